@@ -0,0 +1,73 @@
+//! Compares scoring every discriminator with its own addressing against the
+//! shared addressing of `WisardBase`, on a dataset with dozens of classes.
+
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use wisard::{
+    filter::PackedLUTFilterBuilder,
+    model::{Discriminator, TupleMapping, WisardBase},
+    sample::Sample,
+};
+
+const INPUT_SIZE: usize = 2048;
+const ADDR_SIZE: usize = 16;
+const NUM_LABELS: usize = 48;
+const NUM_SAMPLES: usize = 64;
+
+fn samples() -> Vec<Sample<usize>> {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    (0..NUM_SAMPLES)
+        .map(|i| {
+            let bits = (0..INPUT_SIZE).map(|_| rng.gen::<bool>()).collect();
+            Sample::from_raw_parts(bits, 1, i % NUM_LABELS)
+        })
+        .collect()
+}
+
+fn inference(c: &mut Criterion) {
+    let samples = samples();
+    let builder = PackedLUTFilterBuilder::new(ADDR_SIZE, 1, 0);
+    let mapping = TupleMapping::random(INPUT_SIZE, ADDR_SIZE, [7; 32]);
+    let labels = (0..NUM_LABELS).collect::<HashSet<_>>();
+
+    let mut base = WisardBase::from_mapping(mapping.clone(), labels, &builder);
+    base.fit_batch(&samples);
+
+    let mut discs = (0..NUM_LABELS)
+        .map(|_| Discriminator::from_mapping(mapping.clone(), &builder))
+        .collect::<Vec<_>>();
+
+    for sample in samples.iter() {
+        discs[*sample.label()].fit(sample);
+    }
+
+    let mut group = c.benchmark_group("scores");
+
+    group.bench_function("per_discriminator", |b| {
+        b.iter(|| {
+            for sample in samples.iter() {
+                let scores = discs
+                    .iter()
+                    .map(|disc| disc.score(sample))
+                    .collect::<Vec<_>>();
+                black_box(scores);
+            }
+        })
+    });
+
+    group.bench_function("shared_addresses", |b| {
+        b.iter(|| {
+            for sample in samples.iter() {
+                black_box(base.scores(sample));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, inference);
+criterion_main!(benches);
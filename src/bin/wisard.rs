@@ -0,0 +1,409 @@
+//! The `wisard` command-line interface, for training, evaluating and
+//! inspecting models without writing any Rust code.
+//!
+//! Sample labels must be integers. Feature values are encoded with a linear
+//! thermometer, so the same encoding options must be given when training a
+//! model and when using it for predictions.
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use bitvec::prelude::*;
+use clap::{value_parser, Args, Parser, Subcommand, ValueEnum};
+use wisard::{
+    dataset::{ColumnEncoder, CsvReader, Dataset, LibsvmReader},
+    encode::{LinearThermometer, SampleEncoder},
+    eval::ConfusionMatrix,
+    model::{BinaryWisard, CountingWisard, ModelHeader, ModelKind},
+    sample::Sample,
+};
+
+type Label = i64;
+type CliSample = Sample<Label, usize, Lsb0>;
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// The largest RAM address size accepted by `train`.
+const MAX_ADDR_SIZE: i64 = 24;
+
+/// The largest RAM counter size accepted by `train`.
+const MAX_COUNT_SIZE: i64 = 32;
+
+#[derive(Parser)]
+#[command(name = "wisard", version, about = "Train and evaluate WiSARD nets")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Trains a new model and saves it to disk
+    Train(TrainArgs),
+    /// Prints the model prediction for every sample of a dataset
+    Predict(ModelArgs),
+    /// Prints the confusion matrix and metrics of a model over a dataset
+    Eval(ModelArgs),
+    /// Prints the hyperparameters of a saved model
+    Inspect {
+        /// The model file
+        model: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct TrainArgs {
+    #[command(flatten)]
+    data: DataArgs,
+
+    /// The file where the trained model is saved
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// The kind of model to train
+    #[arg(long, value_enum, default_value_t = Kind::Binary)]
+    kind: Kind,
+
+    /// The address size of the RAMs
+    #[arg(
+        long,
+        default_value_t = 8,
+        value_parser = value_parser!(u8).range(1..=MAX_ADDR_SIZE),
+    )]
+    addr_size: u8,
+
+    /// The number of bits of each RAM counter, for counting models
+    #[arg(
+        long,
+        default_value_t = 4,
+        value_parser = value_parser!(u8).range(1..=MAX_COUNT_SIZE),
+    )]
+    count_size: u8,
+
+    /// The permutation seed; a random one is used if omitted
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args)]
+struct ModelArgs {
+    #[command(flatten)]
+    data: DataArgs,
+
+    /// The model file
+    #[arg(short, long)]
+    model: PathBuf,
+}
+
+#[derive(Args)]
+struct DataArgs {
+    /// The dataset file, or the images file for IDX datasets
+    data: PathBuf,
+
+    /// The dataset file format
+    #[arg(long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// The labels file, for IDX datasets
+    #[arg(long, required_if_eq("format", "idx"))]
+    labels: Option<PathBuf>,
+
+    /// The label column of CSV datasets [default: last column]
+    #[arg(long)]
+    label_column: Option<usize>,
+
+    /// Reads the first CSV row as a record instead of a header
+    #[arg(long)]
+    no_headers: bool,
+
+    /// The field delimiter of CSV datasets
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+
+    /// The smallest feature value, for CSV and LIBSVM datasets
+    #[arg(long, default_value_t = 0.0)]
+    min: f64,
+
+    /// The largest feature value, for CSV and LIBSVM datasets
+    #[arg(long, default_value_t = 1.0)]
+    max: f64,
+
+    /// The number of features of LIBSVM datasets [default: the model input
+    /// size, or the largest feature index when training]
+    #[arg(long)]
+    num_features: Option<usize>,
+
+    /// The number of thermometer bits of each feature value
+    #[arg(
+        long,
+        default_value_t = 8,
+        value_parser = value_parser!(u8).range(1..),
+    )]
+    resolution: u8,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Idx,
+    Libsvm,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Kind {
+    Binary,
+    Counting,
+}
+
+/// A model of any kind supported by the model file format.
+enum Model {
+    Binary(BinaryWisard<Label>),
+    Counting(CountingWisard<Label>),
+}
+
+impl Model {
+    fn read(path: &Path) -> CliResult<(ModelHeader, Self)> {
+        let bytes = fs::read(path)?;
+        let header = ModelHeader::read_from(&mut &bytes[..])?;
+
+        let model = match header.kind() {
+            ModelKind::Binary => {
+                Self::Binary(BinaryWisard::read_from(&*bytes)?)
+            }
+            ModelKind::Counting => {
+                Self::Counting(CountingWisard::read_from(&*bytes)?)
+            }
+        };
+
+        Ok((header, model))
+    }
+
+    fn write(&self, path: &Path) -> CliResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match self {
+            Self::Binary(model) => model.write_to(&mut writer)?,
+            Self::Counting(model) => model.write_to(&mut writer)?,
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn fit(&mut self, samples: &[CliSample]) -> CliResult<()> {
+        match self {
+            Self::Binary(model) => model.try_fit_batch(samples)?,
+            Self::Counting(model) => model.try_fit_batch(samples)?,
+        }
+
+        Ok(())
+    }
+
+    fn predict(&self, samples: &[CliSample]) -> CliResult<Vec<Label>> {
+        Ok(match self {
+            Self::Binary(model) => model.try_predict_batch(samples)?,
+            Self::Counting(model) => model.try_predict_batch(samples)?,
+        })
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Train(args) => train(args),
+        Command::Predict(args) => predict(args),
+        Command::Eval(args) => eval(args),
+        Command::Inspect { model } => inspect(&model),
+    };
+
+    if let Err(err) = result {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn train(args: TrainArgs) -> CliResult<()> {
+    let samples = read_samples(&args.data, None)?;
+    let input_size = samples.first().map(Sample::len).unwrap_or(0);
+    let labels = samples.iter().map(|s| *s.label()).collect::<HashSet<_>>();
+    let seed = match args.seed {
+        Some(seed) => {
+            let mut bytes = [0u8; 32];
+            bytes[..8].copy_from_slice(&seed.to_le_bytes());
+            bytes
+        }
+        None => rand::random(),
+    };
+
+    if input_size == 0 {
+        return Err("the dataset has no input bits".into());
+    }
+
+    let mut model = match args.kind {
+        Kind::Binary => Model::Binary(BinaryWisard::with_seed(
+            input_size,
+            args.addr_size as usize,
+            labels,
+            seed,
+        )),
+        Kind::Counting => Model::Counting(CountingWisard::with_seed(
+            input_size,
+            args.addr_size as usize,
+            args.count_size as usize,
+            labels,
+            seed,
+        )),
+    };
+
+    model.fit(&samples)?;
+    model.write(&args.output)?;
+
+    let matrix = confusion_matrix(&samples, model.predict(&samples)?);
+    eprintln!(
+        "trained on {} samples of {} bits, training accuracy: {:.4}",
+        samples.len(),
+        input_size,
+        matrix.accuracy(),
+    );
+    Ok(())
+}
+
+fn predict(args: ModelArgs) -> CliResult<()> {
+    let (header, model) = Model::read(&args.model)?;
+    let samples = read_samples(&args.data, Some(header.input_size()))?;
+    let mut stdout = io::stdout().lock();
+
+    for prediction in model.predict(&samples)? {
+        writeln!(stdout, "{prediction}")?;
+    }
+
+    Ok(())
+}
+
+fn eval(args: ModelArgs) -> CliResult<()> {
+    let (header, model) = Model::read(&args.model)?;
+    let samples = read_samples(&args.data, Some(header.input_size()))?;
+    let matrix = confusion_matrix(&samples, model.predict(&samples)?);
+    print!("{matrix}");
+    Ok(())
+}
+
+fn inspect(path: &Path) -> CliResult<()> {
+    let header = ModelHeader::read_from(&mut File::open(path)?)?;
+    let seed = header
+        .seed()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+
+    println!("kind:        {:?}", header.kind());
+    println!("version:     {}", header.version());
+    println!("input size:  {}", header.input_size());
+    println!("addr size:   {}", header.addr_size());
+    println!("count size:  {}", header.count_size());
+    println!("threshold:   {}", header.threshold());
+    println!("bleaching:   {:?}", header.bleaching());
+    println!("seed:        {seed}");
+    Ok(())
+}
+
+/// Reads and encodes the samples of a dataset.
+///
+/// The `input_size` of a trained model, if any, determines the number of
+/// features of LIBSVM datasets when it is not given explicitly, as a file
+/// may not hold every feature index.
+fn read_samples(
+    args: &DataArgs,
+    input_size: Option<usize>,
+) -> CliResult<Vec<CliSample>> {
+    let encoder = ColumnEncoder::Thermometer {
+        min: args.min,
+        max: args.max,
+        resolution: args.resolution as usize,
+    };
+
+    let dataset: Dataset<Label, usize, Lsb0> = match args.format {
+        Format::Csv => {
+            let delimiter = u8::try_from(args.delimiter)
+                .map_err(|_| "the delimiter must be an ASCII character")?;
+            let columns = count_columns(&args.data, delimiter)?;
+            let label_column =
+                args.label_column.unwrap_or(columns.saturating_sub(1));
+
+            if label_column >= columns {
+                return Err("the label column is out of range".into());
+            }
+
+            let encoders = vec![encoder; columns.saturating_sub(1)];
+            CsvReader::new(label_column, encoders)
+                .with_headers(!args.no_headers)
+                .with_delimiter(delimiter)
+                .read_path(&args.data)?
+        }
+        Format::Libsvm => {
+            let num_features = args.num_features.or_else(|| {
+                input_size.map(|size| size / args.resolution as usize)
+            });
+            let reader = LibsvmReader::new(encoder);
+
+            match num_features {
+                Some(num_features) => reader.with_num_features(num_features),
+                None => reader,
+            }
+            .read_path(&args.data)?
+        }
+        Format::Idx => {
+            let labels = args
+                .labels
+                .as_ref()
+                .ok_or("IDX datasets require a labels file")?;
+            let dataset = Dataset::<u8, usize, Lsb0>::read_idx(
+                File::open(&args.data)?,
+                File::open(labels)?,
+                8,
+            )?;
+            let encoder =
+                LinearThermometer::try_with_resolution(args.resolution)?;
+            let samples = dataset
+                .iter()
+                .map(|sample| {
+                    let (bits, vsize, label) = sample.clone().into_raw_parts();
+                    let sample =
+                        Sample::from_raw_parts(bits, vsize, label as Label);
+                    encoder.try_encode(sample)
+                })
+                .collect::<Result<_, _>>()?;
+            Dataset::from_samples(samples)
+        }
+    };
+
+    Ok(dataset.iter().cloned().collect())
+}
+
+/// Returns the number of columns of the first record of a CSV file.
+fn count_columns(path: &Path, delimiter: u8) -> CliResult<usize> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .from_path(path)?;
+
+    match reader.records().next() {
+        Some(record) => Ok(record?.len()),
+        None => Ok(0),
+    }
+}
+
+/// Builds a confusion matrix from the samples and their predictions.
+fn confusion_matrix(
+    samples: &[CliSample],
+    predictions: Vec<Label>,
+) -> ConfusionMatrix<Label> {
+    ConfusionMatrix::from_predictions(
+        samples.iter().map(|s| *s.label()).zip(predictions),
+    )
+}
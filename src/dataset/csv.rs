@@ -0,0 +1,337 @@
+use std::{fs::File, io::Read, path::Path, str::FromStr};
+
+use bitvec::prelude::*;
+use csv::{ReaderBuilder, StringRecord};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dataset::{Dataset, DatasetError, DatasetResult},
+    sample::{Label, Sample},
+};
+
+/// An encoder that turns the value of a single CSV column into bits.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColumnEncoder {
+    /// Encodes a numeric value with a linear thermometer of `resolution`
+    /// bits, where values at or below `min` set no bits and values at or
+    /// above `max` set every bit.
+    Thermometer {
+        min: f64,
+        max: f64,
+        resolution: usize,
+    },
+    /// Encodes a categorical value with one bit for each category, setting
+    /// only the bit of the matching category.
+    OneHot(Vec<String>),
+    /// Encodes an unsigned integer value with its `bits` least significant
+    /// bits, starting from the least significant one.
+    Raw { bits: usize },
+    /// Ignores the column.
+    Skip,
+}
+
+impl ColumnEncoder {
+    /// Returns the number of bits produced by the encoder.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Thermometer { resolution, .. } => *resolution,
+            Self::OneHot(categories) => categories.len(),
+            Self::Raw { bits } => *bits,
+            Self::Skip => 0,
+        }
+    }
+
+    /// Returns `true` if the encoder produces no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Encodes a column value, appending its bits to `bits`. Returns `None`
+    /// if the value cannot be encoded.
+    pub(crate) fn encode<T, O>(
+        &self,
+        value: &str,
+        bits: &mut BitVec<T, O>,
+    ) -> Option<()>
+    where
+        T: BitStore,
+        O: BitOrder,
+    {
+        match self {
+            Self::Thermometer {
+                min,
+                max,
+                resolution,
+            } => {
+                let value = value.parse::<f64>().ok()?;
+
+                if value.is_nan() {
+                    return None;
+                }
+
+                let ratio = match max > min {
+                    true => ((value - min) / (max - min)).clamp(0.0, 1.0),
+                    false => (value > *min) as usize as f64,
+                };
+                let ones = (ratio * *resolution as f64).round() as usize;
+                bits.extend((0..*resolution).map(|i| i < ones));
+            }
+            Self::OneHot(categories) => {
+                let index = categories.iter().position(|c| c == value)?;
+                bits.extend((0..categories.len()).map(|i| i == index));
+            }
+            Self::Raw { bits: size } => {
+                let value = value.parse::<u64>().ok()?;
+
+                if *size < u64::BITS as usize && value >> size != 0 {
+                    return None;
+                }
+
+                bits.extend(
+                    (0..*size).map(|i| i < 64 && (value >> i) & 1 == 1),
+                );
+            }
+            Self::Skip => {}
+        }
+
+        Some(())
+    }
+}
+
+/// A reader for datasets stored as comma-separated values (CSV).
+///
+/// Each record is turned into a [`Sample`](../sample/struct.Sample.html)
+/// by concatenating the bits produced by a
+/// [`ColumnEncoder`](./enum.ColumnEncoder.html) for every column but the
+/// label column, which is parsed into the sample label. The resulting
+/// samples have a value size of one bit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CsvReader {
+    label_column: usize,
+    encoders: Vec<ColumnEncoder>,
+    has_headers: bool,
+    delimiter: u8,
+}
+
+impl CsvReader {
+    /// Creates a new [`CsvReader`](./struct.CsvReader.html) instance.
+    ///
+    /// The `label_column` parameter is the zero-based index of the column
+    /// holding the sample labels, while `encoders` holds an encoder for
+    /// each of the remaining columns, in order. By default, the first
+    /// record is a header and the fields are separated by commas.
+    pub fn new(label_column: usize, encoders: Vec<ColumnEncoder>) -> Self {
+        assert!(
+            label_column <= encoders.len(),
+            "CsvReader label column is out of the record bounds"
+        );
+
+        Self {
+            label_column,
+            encoders,
+            has_headers: true,
+            delimiter: b',',
+        }
+    }
+
+    /// Sets whether the first record is a header and should be skipped.
+    pub fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Sets the field delimiter.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Returns the number of bits of every sample read.
+    pub fn input_size(&self) -> usize {
+        self.encoders.iter().map(ColumnEncoder::len).sum()
+    }
+
+    /// Reads a dataset from the file at `path`.
+    pub fn read_path<L, T, O, P>(
+        &self,
+        path: P,
+    ) -> DatasetResult<Dataset<L, T, O>>
+    where
+        L: Label + FromStr,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        P: AsRef<Path>,
+    {
+        self.read(File::open(path)?)
+    }
+
+    /// Reads a dataset from a reader.
+    ///
+    /// Returns an error if a record is malformed, has an unexpected number
+    /// of columns, or holds a label or value that cannot be parsed.
+    pub fn read<L, T, O, R>(&self, reader: R) -> DatasetResult<Dataset<L, T, O>>
+    where
+        L: Label + FromStr,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        R: Read,
+    {
+        let mut reader = ReaderBuilder::new()
+            .has_headers(self.has_headers)
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .from_reader(reader);
+
+        let mut dataset = Dataset::new();
+        let mut record = StringRecord::new();
+
+        while reader.read_record(&mut record)? {
+            dataset.push(self.parse_record(&record)?);
+        }
+
+        Ok(dataset)
+    }
+
+    /// Parses a single record into a sample.
+    fn parse_record<L, T, O>(
+        &self,
+        record: &StringRecord,
+    ) -> DatasetResult<Sample<L, T, O>>
+    where
+        L: Label + FromStr,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let row = record.position().map(|p| p.line()).unwrap_or(0);
+        let expected = self.encoders.len() + 1;
+
+        if record.len() != expected {
+            return Err(DatasetError::ColumnCount {
+                row,
+                expected,
+                found: record.len(),
+            });
+        }
+
+        let label = record[self.label_column].trim();
+        let label =
+            label.parse::<L>().map_err(|_| DatasetError::InvalidLabel {
+                row,
+                column: self.label_column,
+                value: label.to_string(),
+            })?;
+
+        let mut bits = BitVec::with_capacity(self.input_size());
+        let columns = (0..record.len()).filter(|&i| i != self.label_column);
+
+        for (column, encoder) in columns.zip(self.encoders.iter()) {
+            let value = record[column].trim();
+            encoder.encode(value, &mut bits).ok_or_else(|| {
+                DatasetError::InvalidValue {
+                    row,
+                    column,
+                    value: value.to_string(),
+                }
+            })?;
+        }
+
+        Ok(Sample::from_raw_parts(bits, 1, label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoders() -> Vec<ColumnEncoder> {
+        vec![
+            ColumnEncoder::Thermometer {
+                min: 0.0,
+                max: 1.0,
+                resolution: 4,
+            },
+            ColumnEncoder::OneHot(vec!["red".into(), "green".into()]),
+            ColumnEncoder::Raw { bits: 3 },
+        ]
+    }
+
+    #[test]
+    fn csv_reader_encodings() {
+        let data = "x,color,n,label\n0.5,green,5,1\n1.5, red ,0,0\n";
+        let reader = CsvReader::new(3, encoders());
+        let dataset: Dataset<u8, usize, Lsb0> =
+            reader.read(data.as_bytes()).unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(reader.input_size(), 9);
+        assert_eq!(dataset[0].label(), &1);
+        assert_eq!(dataset[0].raw_bits(), bits![1, 1, 0, 0, 0, 1, 1, 0, 1],);
+        assert_eq!(dataset[1].label(), &0);
+        assert_eq!(dataset[1].raw_bits(), bits![1, 1, 1, 1, 1, 0, 0, 0, 0],);
+    }
+
+    #[test]
+    fn csv_reader_label_column() {
+        let data = "2;0.0;red;7\n";
+        let reader = CsvReader::new(0, encoders())
+            .with_headers(false)
+            .with_delimiter(b';');
+        let dataset: Dataset<u8, usize, Lsb0> =
+            reader.read(data.as_bytes()).unwrap();
+        assert_eq!(dataset[0].label(), &2);
+        assert_eq!(dataset[0].raw_bits(), bits![0, 0, 0, 0, 1, 0, 1, 1, 1],);
+    }
+
+    #[test]
+    fn csv_reader_errors() {
+        let reader = CsvReader::new(3, encoders()).with_headers(false);
+        let read = |data: &str| {
+            reader
+                .read::<u8, usize, Lsb0, _>(data.as_bytes())
+                .unwrap_err()
+        };
+
+        assert!(matches!(
+            read("0.5,red,1,0\n0.5,red,1\n"),
+            DatasetError::ColumnCount {
+                row: 2,
+                expected: 4,
+                found: 3,
+            },
+        ));
+        assert!(matches!(
+            read("0.5,blue,1,0\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 1,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0.5,red,8,0\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 2,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("x,red,1,0\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 0,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0.5,red,1,a\n"),
+            DatasetError::InvalidLabel {
+                row: 1,
+                column: 3,
+                ..
+            },
+        ));
+    }
+}
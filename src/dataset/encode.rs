@@ -0,0 +1,99 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    dataset::Dataset, encode::SampleEncoder, error::WisardResult, sample::Label,
+};
+
+impl<L, T, O> Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Encodes every sample of the dataset in-place with a given `encoder`.
+    pub fn encode_inplace<E>(&mut self, encoder: &E)
+    where
+        E: SampleEncoder<L, T, O>,
+    {
+        for sample in self.samples.iter_mut() {
+            encoder.encode_inplace(sample);
+        }
+    }
+
+    /// Consumes the dataset and returns its encoded version.
+    pub fn encode<E>(mut self, encoder: &E) -> Self
+    where
+        E: SampleEncoder<L, T, O>,
+    {
+        self.encode_inplace(encoder);
+        self
+    }
+
+    /// Encodes every sample of the dataset in-place with a given `encoder`,
+    /// returning an error if any sample cannot be encoded. The dataset is
+    /// left unchanged in case of errors.
+    pub fn try_encode_inplace<E>(&mut self, encoder: &E) -> WisardResult<()>
+    where
+        E: SampleEncoder<L, T, O>,
+        T: Clone,
+        O: Clone,
+    {
+        self.samples = self
+            .samples
+            .iter()
+            .map(|sample| encoder.try_encode(sample.clone()))
+            .collect::<WisardResult<_>>()?;
+        Ok(())
+    }
+
+    /// Consumes the dataset and returns its encoded version, or an error if
+    /// any sample cannot be encoded.
+    pub fn try_encode<E>(self, encoder: &E) -> WisardResult<Self>
+    where
+        E: SampleEncoder<L, T, O>,
+    {
+        self.samples
+            .into_iter()
+            .map(|sample| encoder.try_encode(sample))
+            .collect::<WisardResult<_>>()
+            .map(Self::from_samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encode::{Chain, LinearThermometer, Slice},
+        error::WisardError,
+        sample::Sample,
+    };
+
+    fn dataset() -> Dataset<usize, usize, Lsb0> {
+        Dataset::from_samples(vec![
+            Sample::from_raw_parts(bitvec![0, 1, 1, 1], 2, 0),
+            Sample::from_raw_parts(bitvec![1, 0, 0, 0], 2, 1),
+        ])
+    }
+
+    #[test]
+    fn dataset_encode() {
+        let encoder =
+            Chain::new(Slice::new(0, 1), LinearThermometer::with_resolution(2));
+        let encoded = dataset().encode(&encoder);
+        assert_eq!(encoded[0].raw_bits(), bits![0, 0, 1, 0]);
+        assert_eq!(encoded[1].raw_bits(), bits![1, 0, 0, 0]);
+        assert_eq!(encoded[1].vsize(), 2);
+        assert_eq!(dataset().try_encode(&encoder).unwrap()[1], encoded[1]);
+    }
+
+    #[test]
+    fn dataset_try_encode_errors() {
+        let mut dataset = dataset();
+        let result = dataset.try_encode_inplace(&Slice::new(3, 3));
+        assert!(matches!(result, Err(WisardError::InvalidSlice { .. })));
+        assert_eq!(dataset[0].raw_bits(), bits![0, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,272 @@
+use std::io::{Read, Write};
+
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    dataset::{Dataset, DatasetError, DatasetResult},
+    sample::Sample,
+};
+
+/// The IDX type code for unsigned bytes.
+const IDX_UBYTE: u8 = 0x08;
+
+/// The largest value size supported by the IDX files, in bits.
+const IDX_MAX_VSIZE: usize = u8::BITS as usize;
+
+impl<T, O> Dataset<u8, T, O>
+where
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Reads a dataset from a pair of IDX files, such as the ones used by the
+    /// MNIST dataset.
+    ///
+    /// The `images` file holds an unsigned byte tensor whose first dimension
+    /// is the number of samples, while the `labels` file holds an unsigned
+    /// byte vector with the label of each sample. Each byte of the images is
+    /// quantized to its `vsize` most significant bits, which must be between
+    /// one and eight.
+    pub fn read_idx<I, R>(
+        images: I,
+        labels: R,
+        vsize: usize,
+    ) -> DatasetResult<Self>
+    where
+        I: Read,
+        R: Read,
+    {
+        if vsize == 0 || vsize > IDX_MAX_VSIZE {
+            return Err(DatasetError::InvalidIdx("unsupported value size"));
+        }
+
+        let (image_dims, images) = read_idx_tensor(images)?;
+        let (label_dims, labels) = read_idx_tensor(labels)?;
+
+        if label_dims.len() != 1 {
+            return Err(DatasetError::InvalidIdx(
+                "labels file is not one-dimensional",
+            ));
+        }
+
+        if image_dims.first() != Some(&label_dims[0]) {
+            return Err(DatasetError::InvalidIdx(
+                "images and labels have different lengths",
+            ));
+        }
+
+        let item_size = image_dims[1..].iter().product::<usize>();
+
+        if item_size == 0 {
+            return Err(DatasetError::InvalidIdx("images have no values"));
+        }
+
+        if label_dims[0].checked_mul(item_size) != Some(images.len()) {
+            return Err(DatasetError::InvalidIdx(
+                "images and labels have different lengths",
+            ));
+        }
+
+        let shift = IDX_MAX_VSIZE - vsize;
+        let samples = images
+            .chunks_exact(item_size)
+            .zip(labels)
+            .map(|(image, label)| {
+                let mut bits = BitVec::with_capacity(image.len() * vsize);
+
+                for &value in image {
+                    let value = (value >> shift) as usize;
+                    bits.extend_from_bitslice(&value.view_bits::<O>()[..vsize]);
+                }
+
+                Sample::from_raw_parts(bits, vsize, label)
+            })
+            .collect();
+
+        Ok(Self::from_samples(samples))
+    }
+
+    /// Writes the dataset to a pair of IDX files, using the same layout read
+    /// by [`read_idx`](#method.read_idx).
+    ///
+    /// Each sample value is written as a single byte, which requires every
+    /// sample to have the same length and value size, of at most eight bits.
+    pub fn write_idx<I, W>(
+        &self,
+        mut images: I,
+        mut labels: W,
+    ) -> DatasetResult<()>
+    where
+        I: Write,
+        W: Write,
+    {
+        let vsize = self.samples.first().map(|s| s.vsize()).unwrap_or(1);
+        let len = self.samples.first().map(|s| s.len()).unwrap_or(0);
+
+        if vsize == 0 || vsize > IDX_MAX_VSIZE {
+            return Err(DatasetError::InvalidIdx("unsupported value size"));
+        }
+
+        if self.iter().any(|s| s.vsize() != vsize || s.len() != len) {
+            return Err(DatasetError::InvalidIdx(
+                "samples have different lengths or value sizes",
+            ));
+        }
+
+        let shift = IDX_MAX_VSIZE - vsize;
+        let values = len.div_ceil(vsize);
+        write_idx_header(&mut images, &[self.len(), values])?;
+        write_idx_header(&mut labels, &[self.len()])?;
+
+        for sample in self.iter() {
+            let bytes = sample
+                .iter_values()
+                .map(|chunk| {
+                    let mut value = 0usize;
+                    value.view_bits_mut::<O>()[..chunk.len()]
+                        .clone_from_bitslice(chunk);
+                    (value << shift) as u8
+                })
+                .collect::<Vec<_>>();
+            images.write_all(&bytes)?;
+        }
+
+        let bytes = self.iter().map(|s| *s.label()).collect::<Vec<_>>();
+        labels.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// Reads an unsigned byte IDX tensor, returning its dimensions and data.
+fn read_idx_tensor<R: Read>(
+    mut reader: R,
+) -> DatasetResult<(Vec<usize>, Vec<u8>)> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+
+    if magic[..2] != [0, 0] {
+        return Err(DatasetError::InvalidIdx("invalid magic number"));
+    }
+
+    if magic[2] != IDX_UBYTE {
+        return Err(DatasetError::InvalidIdx("unsupported data type"));
+    }
+
+    if magic[3] == 0 {
+        return Err(DatasetError::InvalidIdx("tensor has no dimensions"));
+    }
+
+    let mut dims = Vec::with_capacity(magic[3] as usize);
+    let mut size = 1usize;
+
+    for _ in 0..magic[3] {
+        let mut dim = [0u8; 4];
+        reader.read_exact(&mut dim)?;
+        let dim = u32::from_be_bytes(dim) as usize;
+        size = size
+            .checked_mul(dim)
+            .ok_or(DatasetError::InvalidIdx("tensor is too large"))?;
+        dims.push(dim);
+    }
+
+    let mut data = Vec::new();
+    reader.take(size as u64).read_to_end(&mut data)?;
+
+    if data.len() != size {
+        return Err(DatasetError::InvalidIdx("tensor data is truncated"));
+    }
+
+    Ok((dims, data))
+}
+
+/// Writes the header of an unsigned byte IDX tensor.
+fn write_idx_header<W: Write>(
+    writer: &mut W,
+    dims: &[usize],
+) -> DatasetResult<()> {
+    writer.write_all(&[0, 0, IDX_UBYTE, dims.len() as u8])?;
+
+    for &dim in dims {
+        let dim = u32::try_from(dim)
+            .map_err(|_| DatasetError::InvalidIdx("tensor is too large"))?;
+        writer.write_all(&dim.to_be_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idx_files() -> (Vec<u8>, Vec<u8>) {
+        let mut images = vec![0, 0, 8, 3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2];
+        images.extend([0x00, 0xff, 0x80, 0x7f]);
+        let mut labels = vec![0, 0, 8, 1, 0, 0, 0, 2];
+        labels.extend([3, 7]);
+        (images, labels)
+    }
+
+    #[test]
+    fn read_idx_files() {
+        let (images, labels) = idx_files();
+        let dataset =
+            Dataset::<u8, usize, Lsb0>::read_idx(&images[..], &labels[..], 2)
+                .unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset[0].label(), &3);
+        assert_eq!(dataset[0].vsize(), 2);
+        assert_eq!(dataset[0].raw_bits(), bits![0, 0, 1, 1]);
+        assert_eq!(dataset[1].label(), &7);
+        assert_eq!(dataset[1].raw_bits(), bits![0, 1, 1, 0]);
+    }
+
+    #[test]
+    fn write_idx_round_trip() {
+        let (images, labels) = idx_files();
+        let dataset =
+            Dataset::<u8, usize, Lsb0>::read_idx(&images[..], &labels[..], 8)
+                .unwrap();
+        let mut new_images = Vec::new();
+        let mut new_labels = Vec::new();
+        dataset.write_idx(&mut new_images, &mut new_labels).unwrap();
+        assert_eq!(&new_images[..4], &[0, 0, 8, 2]);
+        assert_eq!(&new_images[12..], &images[16..]);
+        assert_eq!(new_labels, labels);
+    }
+
+    #[test]
+    fn read_idx_invalid_files() {
+        let (images, labels) = idx_files();
+        let read = |images: &[u8], labels: &[u8], vsize| {
+            Dataset::<u8, usize, Lsb0>::read_idx(images, labels, vsize)
+        };
+
+        assert!(read(&images, &labels, 0).is_err());
+        assert!(read(&images, &labels, 9).is_err());
+        assert!(read(&images[..images.len() - 1], &labels, 8).is_err());
+        assert!(read(&images[..10], &labels, 8).is_err());
+
+        let mut invalid = images.clone();
+        invalid[2] = 0x0d;
+        assert!(matches!(
+            read(&invalid, &labels, 8),
+            Err(DatasetError::InvalidIdx(_)),
+        ));
+
+        let mut invalid = labels.clone();
+        invalid[7] = 3;
+        invalid.push(0);
+        assert!(matches!(
+            read(&images, &invalid, 8),
+            Err(DatasetError::InvalidIdx(_)),
+        ));
+
+        let empty = [0, 0, 8, 3, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2];
+        assert!(matches!(
+            read(&empty, &labels, 8),
+            Err(DatasetError::InvalidIdx(_)),
+        ));
+    }
+}
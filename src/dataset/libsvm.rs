@@ -0,0 +1,368 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dataset::{ColumnEncoder, Dataset, DatasetError, DatasetResult},
+    sample::{Label, Sample},
+};
+
+/// The largest number of features accepted by a
+/// [`LibsvmReader`](./struct.LibsvmReader.html).
+pub const MAX_LIBSVM_FEATURES: usize = 1 << 24;
+
+/// A reader for datasets stored in the sparse LIBSVM text format.
+///
+/// Each line holds a label followed by `index:value` pairs, with one-based
+/// feature indices in ascending order. Every feature value is turned into
+/// `vsize` bits by the same [`ColumnEncoder`](./enum.ColumnEncoder.html),
+/// where `vsize` is the number of bits produced by the encoder. Features
+/// missing from a line take a default value, which is zero unless set.
+///
+/// Errors report the one-based line number as the row and the zero-based
+/// position of the offending token in the line as the column, where the
+/// label is at position zero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LibsvmReader {
+    encoder: ColumnEncoder,
+    num_features: Option<usize>,
+    default: String,
+}
+
+impl LibsvmReader {
+    /// Creates a new [`LibsvmReader`](./struct.LibsvmReader.html) instance
+    /// that encodes every feature value with `encoder`.
+    ///
+    /// By default, the number of features is inferred from the largest
+    /// feature index in the file.
+    pub fn new(encoder: ColumnEncoder) -> Self {
+        assert!(
+            !encoder.is_empty(),
+            "LibsvmReader requires an encoder that produces bits"
+        );
+
+        Self {
+            encoder,
+            num_features: None,
+            default: "0".to_string(),
+        }
+    }
+
+    /// Sets a fixed number of features, instead of inferring it.
+    pub fn with_num_features(mut self, num_features: usize) -> Self {
+        self.num_features = Some(num_features);
+        self
+    }
+
+    /// Sets the value used for features missing from a line.
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = default.into();
+        self
+    }
+
+    /// Reads a dataset from the file at `path`.
+    pub fn read_path<L, T, O, P>(
+        &self,
+        path: P,
+    ) -> DatasetResult<Dataset<L, T, O>>
+    where
+        L: Label + FromStr,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        P: AsRef<Path>,
+    {
+        self.read(File::open(path)?)
+    }
+
+    /// Reads a dataset from a reader.
+    ///
+    /// Returns an error if the default value cannot be encoded, if a line
+    /// holds a label, index or value that cannot be parsed, an index out of
+    /// the number of features, or if the number of features is larger than
+    /// [`MAX_LIBSVM_FEATURES`](./constant.MAX_LIBSVM_FEATURES.html).
+    pub fn read<L, T, O, R>(&self, reader: R) -> DatasetResult<Dataset<L, T, O>>
+    where
+        L: Label + FromStr,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        R: Read,
+    {
+        let mut default = BitVec::<T, O>::new();
+
+        if self.encoder.encode(&self.default, &mut default).is_none() {
+            return Err(DatasetError::InvalidDefault(self.default.clone()));
+        }
+
+        let mut rows = Vec::new();
+
+        for (line, text) in BufReader::new(reader).lines().enumerate() {
+            let row = line as u64 + 1;
+            let text = text?;
+            let text = text.split('#').next().unwrap_or("").trim();
+
+            if !text.is_empty() {
+                rows.push(parse_line::<L>(row, text)?);
+            }
+        }
+
+        let num_features = self.num_features.unwrap_or_else(|| {
+            rows.iter()
+                .filter_map(|(_, _, features)| features.last())
+                .map(|(index, _, _)| *index)
+                .max()
+                .unwrap_or(0)
+        });
+
+        let vsize = self.encoder.len();
+        let mut dataset = Dataset::new();
+
+        for (row, label, features) in rows {
+            let size = Some(num_features)
+                .filter(|&n| n <= MAX_LIBSVM_FEATURES)
+                .and_then(|n| n.checked_mul(vsize))
+                .ok_or(DatasetError::TooManyFeatures { row, num_features })?;
+            let mut bits = BitVec::with_capacity(size);
+            let mut features = features.into_iter().peekable();
+
+            for index in 1..=num_features {
+                let (value, column) = match features.peek() {
+                    Some((i, _, _)) if *i == index => {
+                        let (_, value, column) = features.next().unwrap();
+                        (value, column)
+                    }
+                    _ => {
+                        bits.extend_from_bitslice(&default);
+                        continue;
+                    }
+                };
+
+                if self.encoder.encode(&value, &mut bits).is_none() {
+                    return Err(DatasetError::InvalidValue {
+                        row,
+                        column,
+                        value,
+                    });
+                }
+            }
+
+            if let Some((_, value, column)) = features.next() {
+                return Err(DatasetError::InvalidValue { row, column, value });
+            }
+
+            dataset.push(Sample::from_raw_parts(bits, vsize, label));
+        }
+
+        Ok(dataset)
+    }
+}
+
+/// A LIBSVM line, holding its row, label and `(index, value, column)` pairs.
+type LibsvmLine<L> = (u64, L, Vec<(usize, String, usize)>);
+
+/// Parses the label and features of a LIBSVM line.
+fn parse_line<L>(row: u64, text: &str) -> DatasetResult<LibsvmLine<L>>
+where
+    L: Label + FromStr,
+{
+    let mut tokens = text.split_whitespace();
+    let label = tokens.next().unwrap_or("");
+    let label = label.parse().map_err(|_| DatasetError::InvalidLabel {
+        row,
+        column: 0,
+        value: label.to_string(),
+    })?;
+
+    let mut features = Vec::new();
+
+    for (column, token) in tokens.enumerate().map(|(i, t)| (i + 1, t)) {
+        let invalid = || DatasetError::InvalidValue {
+            row,
+            column,
+            value: token.to_string(),
+        };
+
+        let (index, value) = token.split_once(':').ok_or_else(invalid)?;
+        let index = index.parse::<usize>().map_err(|_| invalid())?;
+        let last = features.last().map(|(i, _, _)| *i).unwrap_or(0);
+
+        if index <= last {
+            return Err(invalid());
+        }
+
+        if index > MAX_LIBSVM_FEATURES {
+            return Err(DatasetError::TooManyFeatures {
+                row,
+                num_features: index,
+            });
+        }
+
+        features.push((index, value.to_string(), column));
+    }
+
+    Ok((row, label, features))
+}
+
+impl<L, T, O> Dataset<L, T, O>
+where
+    L: Label + Display,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Writes the dataset in the sparse LIBSVM text format.
+    ///
+    /// Each sample value is written as an unsigned integer feature, and
+    /// features with a value of zero are omitted. Reading the file back
+    /// with a [`ColumnEncoder::Raw`](./enum.ColumnEncoder.html) encoder of
+    /// `vsize` bits and the number of sample values as the number of
+    /// features restores the original samples, as long as their length is a
+    /// multiple of `vsize`. If the number of features is inferred instead,
+    /// trailing zero values are lost.
+    pub fn write_libsvm<W: Write>(&self, mut writer: W) -> DatasetResult<()> {
+        for sample in self.iter() {
+            if sample.vsize() == 0 || sample.vsize() > u64::BITS as usize {
+                return Err(DatasetError::UnsupportedValueSize(sample.vsize()));
+            }
+
+            write!(writer, "{}", sample.label())?;
+
+            for (index, chunk) in sample.iter_values().enumerate() {
+                let mut value = 0u64;
+                value.view_bits_mut::<O>()[..chunk.len()]
+                    .clone_from_bitslice(chunk);
+
+                if value != 0 {
+                    write!(writer, " {}:{}", index + 1, value)?;
+                }
+            }
+
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libsvm_reader_inference() {
+        let data = "1 1:0.5 3:1.0\n\n-1 2:0.25 # comment\n";
+        let encoder = ColumnEncoder::Thermometer {
+            min: 0.0,
+            max: 1.0,
+            resolution: 2,
+        };
+        let reader = LibsvmReader::new(encoder);
+        let dataset: Dataset<i8, usize, Lsb0> =
+            reader.read(data.as_bytes()).unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset[0].label(), &1);
+        assert_eq!(dataset[0].vsize(), 2);
+        assert_eq!(dataset[0].raw_bits(), bits![1, 0, 0, 0, 1, 1]);
+        assert_eq!(dataset[1].label(), &-1);
+        assert_eq!(dataset[1].raw_bits(), bits![0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn libsvm_reader_defaults() {
+        let data = "0 2:3\n";
+        let reader = LibsvmReader::new(ColumnEncoder::Raw { bits: 2 })
+            .with_num_features(3)
+            .with_default("1");
+        let dataset: Dataset<u8, usize, Lsb0> =
+            reader.read(data.as_bytes()).unwrap();
+        assert_eq!(dataset[0].raw_bits(), bits![1, 0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn libsvm_round_trip() {
+        let data = "3 1:2 3:7\n1 2:5\n";
+        let reader = LibsvmReader::new(ColumnEncoder::Raw { bits: 3 });
+        let dataset: Dataset<u8, usize, Lsb0> =
+            reader.read(data.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        dataset.write_libsvm(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), data);
+    }
+
+    #[test]
+    fn libsvm_reader_errors() {
+        let reader = LibsvmReader::new(ColumnEncoder::Raw { bits: 2 })
+            .with_num_features(2);
+        let read = |data: &str| {
+            reader
+                .read::<u8, usize, Lsb0, _>(data.as_bytes())
+                .unwrap_err()
+        };
+
+        assert!(matches!(
+            read("0 1:1\na 1:1\n"),
+            DatasetError::InvalidLabel {
+                row: 2,
+                column: 0,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0 1:1 x\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 2,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0 2:1 1:1\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 2,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0 1:4\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 1,
+                ..
+            },
+        ));
+        assert!(matches!(
+            read("0 3:1\n"),
+            DatasetError::InvalidValue {
+                row: 1,
+                column: 1,
+                ..
+            },
+        ));
+
+        let reader = LibsvmReader::new(ColumnEncoder::Raw { bits: 2 });
+        let err = reader
+            .read::<u8, usize, Lsb0, _>("0 1:1\n1 4294967295:1\n".as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, DatasetError::TooManyFeatures { row: 2, .. }));
+        let err = reader
+            .with_num_features(usize::MAX)
+            .read::<u8, usize, Lsb0, _>("0 1:1\n".as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, DatasetError::TooManyFeatures { row: 1, .. }));
+
+        let err = LibsvmReader::new(ColumnEncoder::Raw { bits: 2 })
+            .with_default("4")
+            .read::<u8, usize, Lsb0, _>("0 1:1\n".as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, DatasetError::InvalidDefault(_)));
+    }
+}
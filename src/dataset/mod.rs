@@ -0,0 +1,206 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::sample::Label;
+use crate::sample::Sample;
+
+mod csv;
+mod encode;
+mod idx;
+mod libsvm;
+mod split;
+
+pub use self::csv::*;
+pub use libsvm::*;
+pub use split::*;
+
+pub type DatasetResult<T> = Result<T, DatasetError>;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DatasetError {
+    IO(std::io::Error),
+    /// A CSV record is malformed.
+    Csv(::csv::Error),
+    /// A record does not have the expected number of columns.
+    ColumnCount {
+        row: u64,
+        expected: usize,
+        found: usize,
+    },
+    /// A column value cannot be parsed or encoded.
+    InvalidValue {
+        row: u64,
+        column: usize,
+        value: String,
+    },
+    /// A label cannot be parsed.
+    InvalidLabel {
+        row: u64,
+        column: usize,
+        value: String,
+    },
+    /// An IDX file is malformed or cannot be written.
+    InvalidIdx(&'static str),
+    /// A sample value size is not supported by the file format.
+    UnsupportedValueSize(usize),
+    /// A sample has more features than supported.
+    TooManyFeatures {
+        row: u64,
+        num_features: usize,
+    },
+    /// The value used for missing features cannot be encoded.
+    InvalidDefault(String),
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "I/O error: {err}"),
+            Self::Csv(err) => write!(f, "CSV error: {err}"),
+            Self::ColumnCount {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {row}: expected {expected} columns, found {found}",
+            ),
+            Self::InvalidValue { row, column, value } => {
+                write!(f, "row {row}, column {column}: invalid value {value:?}")
+            }
+            Self::InvalidLabel { row, column, value } => {
+                write!(f, "row {row}, column {column}: invalid label {value:?}")
+            }
+            Self::InvalidIdx(reason) => write!(f, "invalid IDX file: {reason}"),
+            Self::UnsupportedValueSize(vsize) => {
+                write!(f, "unsupported value size of {vsize} bits")
+            }
+            Self::TooManyFeatures { row, num_features } => write!(
+                f,
+                "row {row}: {num_features} features exceed the supported \
+                 maximum",
+            ),
+            Self::InvalidDefault(value) => {
+                write!(f, "invalid default value {value:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            Self::Csv(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DatasetError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl From<::csv::Error> for DatasetError {
+    fn from(err: ::csv::Error) -> Self {
+        Self::Csv(err)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    #[serde(bound = "")]
+    samples: Vec<Sample<L, T, O>>,
+}
+
+impl<L, T, O> Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_samples(samples: Vec<Sample<L, T, O>>) -> Self {
+        Self { samples }
+    }
+
+    pub fn push(&mut self, sample: Sample<L, T, O>) {
+        self.samples.push(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Sample<L, T, O>> {
+        self.samples.as_slice().iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Sample<L, T, O>> {
+        self.samples.as_mut_slice().iter_mut()
+    }
+
+    pub fn labels(&self) -> HashSet<L> {
+        self.samples.iter().map(|s| *s.label()).collect()
+    }
+}
+
+impl<L, T, O> Default for Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl<L, T, O> Index<usize> for Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    type Output = Sample<L, T, O>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.samples.index(index)
+    }
+}
+
+impl<L, T, O> IndexMut<usize> for Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.samples.index_mut(index)
+    }
+}
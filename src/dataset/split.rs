@@ -0,0 +1,307 @@
+use std::{collections::HashMap, ops::Index};
+
+use bitvec::prelude::*;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    dataset::Dataset,
+    sample::{Label, Sample},
+};
+
+/// A borrowed subset of the samples of a
+/// [`Dataset`](./struct.Dataset.html), such as a train or validation split.
+#[derive(Clone, Debug)]
+pub struct DatasetView<'a, L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    dataset: &'a Dataset<L, T, O>,
+    indices: Vec<usize>,
+}
+
+impl<'a, L, T, O> DatasetView<'a, L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Returns the indices of the view samples in the original dataset.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the number of samples in the view.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if the view has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns an iterator over the view samples.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Sample<L, T, O>> + '_ {
+        let dataset = self.dataset;
+        self.indices.iter().map(move |&i| &dataset[i])
+    }
+
+    /// Copies the view samples into a new
+    /// [`Dataset`](./struct.Dataset.html).
+    pub fn to_dataset(&self) -> Dataset<L, T, O>
+    where
+        T: Clone,
+        O: Clone,
+    {
+        Dataset::from_samples(self.iter().cloned().collect())
+    }
+}
+
+impl<'a, L, T, O> Index<usize> for DatasetView<'a, L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    type Output = Sample<L, T, O>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.dataset[self.indices[index]]
+    }
+}
+
+/// A pair of train and validation views.
+pub type SplitViews<'a, L, T, O> =
+    (DatasetView<'a, L, T, O>, DatasetView<'a, L, T, O>);
+
+impl<L, T, O> Dataset<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Shuffles the samples in-place, using a given random `seed`.
+    pub fn shuffle(&mut self, seed: [u8; 32]) {
+        let mut rng = Xoshiro256PlusPlus::from_seed(seed);
+        self.samples.shuffle(&mut rng);
+    }
+
+    /// Splits the dataset into train and validation views, where the train
+    /// view holds the first `ratio` fraction of the samples, rounded to the
+    /// nearest integer, and the validation view holds the remaining ones.
+    pub fn split(&self, ratio: f64) -> SplitViews<'_, L, T, O> {
+        let train_len = split_len(self.len(), ratio);
+        let indices = (0..self.len()).collect::<Vec<_>>();
+        let (train, validation) = indices.split_at(train_len);
+        (self.view(train.to_vec()), self.view(validation.to_vec()))
+    }
+
+    /// Splits the dataset into train and validation views, taking a random
+    /// `ratio` fraction of the samples of each label, generated from a
+    /// given `seed`, so both views preserve the label proportions. The
+    /// samples keep their original order within each view.
+    pub fn stratified_split(
+        &self,
+        ratio: f64,
+        seed: [u8; 32],
+    ) -> SplitViews<'_, L, T, O> {
+        let mut train = Vec::with_capacity(split_len(self.len(), ratio));
+        let mut validation = Vec::new();
+
+        for group in self.shuffled_groups(seed) {
+            let (left, right) = group.split_at(split_len(group.len(), ratio));
+            train.extend_from_slice(left);
+            validation.extend_from_slice(right);
+        }
+
+        train.sort_unstable();
+        validation.sort_unstable();
+        (self.view(train), self.view(validation))
+    }
+
+    /// Returns an iterator over `k` pairs of train and validation views,
+    /// where the validation views are contiguous folds of the dataset.
+    ///
+    /// Fold sizes differ by at most one sample. Shuffle the dataset first
+    /// for randomized folds.
+    pub fn k_fold(
+        &self,
+        k: usize,
+    ) -> impl Iterator<Item = SplitViews<'_, L, T, O>> + '_ {
+        assert!(k > 0, "k-fold requires a non-zero number of folds");
+        let folds = (0..self.len()).map(|i| i * k / self.len()).collect();
+        self.fold_views(k, folds)
+    }
+
+    /// Returns an iterator over `k` pairs of train and validation views,
+    /// where the samples of each label are randomly distributed among the
+    /// folds using a given `seed`, so every fold preserves the label
+    /// proportions.
+    pub fn stratified_k_fold(
+        &self,
+        k: usize,
+        seed: [u8; 32],
+    ) -> impl Iterator<Item = SplitViews<'_, L, T, O>> + '_ {
+        assert!(k > 0, "k-fold requires a non-zero number of folds");
+        let mut folds = vec![0; self.len()];
+        let mut next = 0;
+
+        for group in self.shuffled_groups(seed) {
+            for index in group {
+                folds[index] = next;
+                next = (next + 1) % k;
+            }
+        }
+
+        self.fold_views(k, folds)
+    }
+
+    /// Returns a view over the samples at the given indices.
+    fn view(&self, indices: Vec<usize>) -> DatasetView<'_, L, T, O> {
+        DatasetView {
+            dataset: self,
+            indices,
+        }
+    }
+
+    /// Returns the sample indices of each label, in order of the first
+    /// appearance of the label, shuffled using a given `seed`.
+    fn shuffled_groups(&self, seed: [u8; 32]) -> Vec<Vec<usize>> {
+        let mut rng = Xoshiro256PlusPlus::from_seed(seed);
+        let mut positions = HashMap::<L, usize>::new();
+        let mut groups = Vec::<Vec<usize>>::new();
+
+        for (index, sample) in self.iter().enumerate() {
+            let position =
+                *positions.entry(*sample.label()).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+            groups[position].push(index);
+        }
+
+        for group in groups.iter_mut() {
+            group.shuffle(&mut rng);
+        }
+
+        groups
+    }
+
+    /// Returns an iterator over the train and validation views of each fold,
+    /// given the fold of every sample.
+    fn fold_views(
+        &self,
+        k: usize,
+        folds: Vec<usize>,
+    ) -> impl Iterator<Item = SplitViews<'_, L, T, O>> + '_ {
+        (0..k).map(move |fold| {
+            let (validation, train): (Vec<_>, Vec<_>) =
+                (0..self.len()).partition(|&i| folds[i] == fold);
+            (self.view(train), self.view(validation))
+        })
+    }
+}
+
+/// Returns the number of samples of a split with a given `ratio`.
+fn split_len(len: usize, ratio: f64) -> usize {
+    assert!(
+        (0.0..=1.0).contains(&ratio),
+        "split ratio must be between zero and one"
+    );
+
+    ((len as f64 * ratio).round() as usize).min(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset() -> Dataset<usize, usize, Lsb0> {
+        let samples = (0..20usize)
+            .map(|i| {
+                let bits = i.view_bits::<Lsb0>()[..5].to_bitvec();
+                Sample::from_raw_parts(bits, 1, (i % 4 == 0) as usize)
+            })
+            .collect();
+        Dataset::from_samples(samples)
+    }
+
+    fn count_label<'a>(
+        samples: impl Iterator<Item = &'a Sample<usize, usize, Lsb0>>,
+        label: usize,
+    ) -> usize {
+        samples.filter(|s| *s.label() == label).count()
+    }
+
+    #[test]
+    fn dataset_shuffle() {
+        let mut left = dataset();
+        let mut right = dataset();
+        left.shuffle([1u8; 32]);
+        right.shuffle([1u8; 32]);
+        assert!(left.iter().eq(right.iter()));
+        assert!(!left.iter().eq(dataset().iter()));
+        assert_eq!(count_label(left.iter(), 1), 5);
+    }
+
+    #[test]
+    fn dataset_split() {
+        let dataset = dataset();
+        let (train, validation) = dataset.split(0.75);
+        assert_eq!(train.indices(), (0..15).collect::<Vec<_>>());
+        assert_eq!(validation.indices(), (15..20).collect::<Vec<_>>());
+        assert_eq!(validation[0].raw_bits(), dataset[15].raw_bits());
+        assert_eq!(train.to_dataset().len(), 15);
+    }
+
+    #[test]
+    fn dataset_stratified_split() {
+        let dataset = dataset();
+        let (train, validation) = dataset.stratified_split(0.6, [2u8; 32]);
+        assert_eq!(train.len(), 12);
+        assert_eq!(validation.len(), 8);
+        assert_eq!(count_label(train.iter(), 1), 3);
+        assert_eq!(count_label(validation.iter(), 1), 2);
+        assert!(train.indices().windows(2).all(|w| w[0] < w[1]));
+
+        let (again, _) = dataset.stratified_split(0.6, [2u8; 32]);
+        assert_eq!(train.indices(), again.indices());
+    }
+
+    #[test]
+    fn dataset_k_fold() {
+        let dataset = dataset();
+        let folds = dataset.k_fold(3).collect::<Vec<_>>();
+        assert_eq!(folds.len(), 3);
+        let sizes = folds.iter().map(|(_, v)| v.len()).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![7, 7, 6]);
+        assert_eq!(folds[1].1.indices(), (7..14).collect::<Vec<_>>());
+        assert_eq!(folds[1].0.len(), 13);
+
+        let mut covered = folds
+            .iter()
+            .flat_map(|(_, v)| v.indices().to_vec())
+            .collect::<Vec<_>>();
+        covered.sort_unstable();
+        assert_eq!(covered, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn dataset_stratified_k_fold() {
+        let dataset = dataset();
+
+        for (train, validation) in dataset.stratified_k_fold(5, [3u8; 32]) {
+            assert_eq!(train.len(), 16);
+            assert_eq!(validation.len(), 4);
+            assert_eq!(count_label(validation.iter(), 1), 1);
+        }
+    }
+}
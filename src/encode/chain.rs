@@ -0,0 +1,120 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::encode::SampleEncoder;
+use crate::error::WisardResult;
+use crate::sample::{Label, Sample};
+
+/// An encoder that applies two encoders in sequence, feeding the output of
+/// the first one into the second one.
+///
+/// Longer pipelines are built by chaining further encoders with
+/// [`then`](#method.then), such that the whole preprocessing is a single
+/// encoder that can be serialized and reused at inference time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Chain<A, B> {
+    /// Creates a new [`Chain`](./struct.Chain.html) encoder instance that
+    /// applies `first` and then `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Consumes the chain and returns a new one that applies `next` after
+    /// the current encoders.
+    pub fn then<C>(self, next: C) -> Chain<Self, C> {
+        Chain::new(self, next)
+    }
+
+    /// Returns the first encoder of the chain.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns the second encoder of the chain.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Consumes the chain and returns its encoders.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<L, T, O, A, B> SampleEncoder<L, T, O> for Chain<A, B>
+where
+    L: Label,
+    T: BitStore + Clone + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder + Clone,
+    A: SampleEncoder<L, T, O>,
+    B: SampleEncoder<L, T, O>,
+{
+    fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
+        self.first.encode_inplace(sample);
+        self.second.encode_inplace(sample);
+    }
+
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        let encoded = self.first.try_encode(sample.clone())?;
+        *sample = self.second.try_encode(encoded)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use super::*;
+    use crate::encode::{LinearThermometer, Permute, Slice};
+
+    fn sample() -> Sample<usize, usize, Lsb0> {
+        Sample::from_raw_parts(bitvec![0, 0, 1, 0, 0, 1, 1, 1], 2, 0)
+    }
+
+    #[test]
+    fn chain_encoders() {
+        let seed = [7u8; 32];
+        let chain =
+            Chain::new(Slice::new(0, 2), LinearThermometer::with_resolution(3))
+                .then(<Permute>::with_seed(seed));
+
+        let expected = <Permute>::with_seed(seed).encode(
+            LinearThermometer::with_resolution(3)
+                .encode(Slice::new(0, 2).encode(sample())),
+        );
+        assert_eq!(chain.encode(sample()), expected);
+        assert_eq!(chain.try_encode(sample()).unwrap(), expected);
+    }
+
+    #[test]
+    fn chain_serialization() {
+        let chain = Chain::new(
+            LinearThermometer::with_resolution(4),
+            <Permute>::with_seed_and_size([3u8; 32], 16),
+        );
+        let bytes = bincode::serialize(&chain).unwrap();
+        let restored: Chain<LinearThermometer, Permute> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored.second().table(), chain.second().table());
+        assert_eq!(restored.encode(sample()), chain.encode(sample()));
+    }
+
+    #[test]
+    fn chain_error_keeps_sample() {
+        let chain =
+            Chain::new(LinearThermometer::with_resolution(4), Slice::new(2, 1));
+        let mut sample = sample();
+        assert!(chain.try_encode_inplace(&mut sample).is_err());
+        assert_eq!(sample, self::sample());
+    }
+}
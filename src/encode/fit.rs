@@ -0,0 +1,366 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::dataset::Dataset;
+use crate::encode::{FitEncoder, SampleEncoder};
+use crate::error::{WisardError, WisardResult};
+use crate::sample::{Label, Sample};
+
+/// A thermometer encoder scaled by the minimum and maximum values of each
+/// feature, learned from a dataset.
+///
+/// Each value is scaled linearly from its feature range into the number of
+/// set bits, between zero and `resolution`. Values out of the learned range
+/// are clamped to it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MinMaxThermometer {
+    /// The resolution (output size), in bits.
+    resolution: u8,
+    /// The minimum and maximum values of each feature.
+    ranges: Vec<(usize, usize)>,
+}
+
+impl MinMaxThermometer {
+    /// Creates a new, unfitted
+    /// [`MinMaxThermometer`](./struct.MinMaxThermometer.html) instance with a
+    /// resolution (output size) of `resolution` bits.
+    pub fn with_resolution(resolution: u8) -> Self {
+        Self {
+            resolution,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Returns the learned minimum and maximum values of each feature.
+    pub fn ranges(&self) -> &[(usize, usize)] {
+        &self.ranges
+    }
+}
+
+impl<L, T, O> FitEncoder<L, T, O> for MinMaxThermometer
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn try_fit(&mut self, dataset: &Dataset<L, T, O>) -> WisardResult<()> {
+        self.ranges = feature_values(dataset)?
+            .into_iter()
+            .map(|values| {
+                let min = values.iter().copied().min().unwrap_or(0);
+                let max = values.iter().copied().max().unwrap_or(0);
+                (min, max)
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+impl<L, T, O> SampleEncoder<L, T, O> for MinMaxThermometer
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
+        self.try_encode_inplace(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        let resolution = self.resolution as usize;
+        encode_features(sample, self.ranges.len(), resolution, |i, value| {
+            let (min, max) = self.ranges[i];
+            let range = max.saturating_sub(min) as f64;
+            let scaled = match range > 0.0 {
+                true => (value.clamp(min, max) - min) as f64 / range,
+                false => 0.0,
+            };
+            (scaled * resolution as f64).round() as usize
+        })
+    }
+}
+
+/// A distributive thermometer encoder, whose thresholds are the quantiles
+/// of each feature, learned from a dataset.
+///
+/// The values of each feature are split into `resolution + 1` groups with
+/// roughly the same number of samples, and the number of set bits of a
+/// value is the number of quantiles it is larger than or equal to. This
+/// spreads the thermometer levels according to the data distribution, rather
+/// than the value range. Features with at most `resolution + 1` distinct
+/// values, such as binary ones, use their distinct values as quantiles
+/// instead, so every value lands on a different level.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DistributiveThermometer {
+    /// The resolution (output size), in bits.
+    resolution: u8,
+    /// The ascending quantiles of each feature.
+    quantiles: Vec<Vec<usize>>,
+}
+
+impl DistributiveThermometer {
+    /// Creates a new, unfitted
+    /// [`DistributiveThermometer`](./struct.DistributiveThermometer.html)
+    /// instance with a resolution (output size) of `resolution` bits.
+    pub fn with_resolution(resolution: u8) -> Self {
+        Self {
+            resolution,
+            quantiles: Vec::new(),
+        }
+    }
+
+    /// Returns the learned quantiles of each feature, in ascending order.
+    pub fn quantiles(&self) -> &[Vec<usize>] {
+        &self.quantiles
+    }
+}
+
+impl<L, T, O> FitEncoder<L, T, O> for DistributiveThermometer
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn try_fit(&mut self, dataset: &Dataset<L, T, O>) -> WisardResult<()> {
+        let resolution = self.resolution as usize;
+        self.quantiles = feature_values(dataset)?
+            .into_iter()
+            .map(|mut values| {
+                values.sort_unstable();
+                let mut distinct = values.clone();
+                distinct.dedup();
+
+                if distinct.len() > resolution + 1 {
+                    return (1..=resolution)
+                        .map(|k| values[k * values.len() / (resolution + 1)])
+                        .collect();
+                }
+
+                // Every value except the smallest starts a new level, and the
+                // largest one fills the remaining levels.
+                let last = distinct[distinct.len() - 1];
+                let mut quantiles = distinct.split_off(1);
+                quantiles.resize(resolution, last);
+                quantiles
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+impl<L, T, O> SampleEncoder<L, T, O> for DistributiveThermometer
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
+        self.try_encode_inplace(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        let resolution = self.resolution as usize;
+        let features = self.quantiles.len();
+        encode_features(sample, features, resolution, |i, value| {
+            self.quantiles[i].partition_point(|&quantile| quantile <= value)
+        })
+    }
+}
+
+/// Returns the integer value of a sample value chunk.
+fn chunk_value<T, O>(chunk: &BitSlice<T, O>) -> usize
+where
+    T: BitStore,
+    O: BitOrder,
+{
+    let mut value = 0usize;
+    value.view_bits_mut::<O>()[..chunk.len()].clone_from_bitslice(chunk);
+    value
+}
+
+/// Checks if a sample value size can be turned into an integer value.
+fn check_vsize(vsize: usize) -> WisardResult<()> {
+    let max = usize::BITS as usize;
+
+    match vsize {
+        0 => Err(WisardError::ZeroValueSize),
+        vsize if vsize > max => {
+            Err(WisardError::ValueSizeTooLarge { vsize, max })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the values of every sample of a dataset, grouped by feature.
+fn feature_values<L, T, O>(
+    dataset: &Dataset<L, T, O>,
+) -> WisardResult<Vec<Vec<usize>>>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    let first = dataset.iter().next().ok_or(WisardError::EmptyDataset)?;
+    check_vsize(first.vsize())?;
+
+    let features = first.len().div_ceil(first.vsize());
+    let mut values = vec![Vec::with_capacity(dataset.len()); features];
+
+    for sample in dataset.iter() {
+        if sample.len() != first.len() || sample.vsize() != first.vsize() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: first.len(),
+                found: sample.len(),
+            });
+        }
+
+        for (i, chunk) in sample.iter_values().enumerate() {
+            values[i].push(chunk_value(chunk));
+        }
+    }
+
+    Ok(values)
+}
+
+/// Replaces every sample value by a thermometer of `resolution` bits, with
+/// the number of set bits given by `level` for each feature and value.
+fn encode_features<L, T, O, F>(
+    sample: &mut Sample<L, T, O>,
+    features: usize,
+    resolution: usize,
+    level: F,
+) -> WisardResult<()>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+    F: Fn(usize, usize) -> usize,
+{
+    if features == 0 {
+        return Err(WisardError::EncoderNotFitted);
+    }
+
+    check_vsize(sample.vsize())?;
+
+    if sample.len().div_ceil(sample.vsize()) != features {
+        return Err(WisardError::InputSizeMismatch {
+            expected: features * sample.vsize(),
+            found: sample.len(),
+        });
+    }
+
+    let mut bits = BitVec::<T, O>::with_capacity(features * resolution);
+
+    for (i, chunk) in sample.iter_values().enumerate() {
+        let ones = level(i, chunk_value(chunk)).min(resolution);
+        bits.extend((0..resolution).map(|bit| bit < ones));
+    }
+
+    sample.set_raw_bits(bits);
+    sample.set_vsize(resolution);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use super::*;
+
+    fn dataset() -> Dataset<usize, usize, Lsb0> {
+        let samples = [(2usize, 0usize), (4, 1), (6, 1), (10, 1)]
+            .into_iter()
+            .map(|(a, b)| {
+                let mut bits = BitVec::new();
+                bits.extend_from_bitslice(&a.view_bits::<Lsb0>()[..4]);
+                bits.extend_from_bitslice(&b.view_bits::<Lsb0>()[..4]);
+                Sample::from_raw_parts(bits, 4, 0)
+            })
+            .collect();
+        Dataset::from_samples(samples)
+    }
+
+    #[test]
+    fn min_max_therm() {
+        let dataset = dataset();
+        let mut encoder = MinMaxThermometer::with_resolution(4);
+        encoder.fit(&dataset);
+        assert_eq!(encoder.ranges(), &[(2, 10), (0, 1)]);
+
+        let encoded = dataset.encode(&encoder);
+        assert_eq!(encoded[0].raw_bits(), bits![0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(encoded[1].raw_bits(), bits![1, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(encoded[2].raw_bits(), bits![1, 1, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(encoded[3].raw_bits(), bits![1, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(encoded[3].vsize(), 4);
+    }
+
+    #[test]
+    fn distributive_therm() {
+        let dataset = dataset();
+        let mut encoder = DistributiveThermometer::with_resolution(3);
+        encoder.fit(&dataset);
+        assert_eq!(encoder.quantiles(), &[vec![4, 6, 10], vec![1, 1, 1]]);
+
+        let encoded = dataset.encode(&encoder);
+        assert_eq!(encoded[0].raw_bits(), bits![0, 0, 0, 0, 0, 0]);
+        assert_eq!(encoded[1].raw_bits(), bits![1, 0, 0, 1, 1, 1]);
+        assert_eq!(encoded[2].raw_bits(), bits![1, 1, 0, 1, 1, 1]);
+        assert_eq!(encoded[3].raw_bits(), bits![1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn distributive_therm_binary_feature() {
+        let samples = [0usize, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+            .into_iter()
+            .map(|v| {
+                let bits = v.view_bits::<Lsb0>()[..1].to_bitvec();
+                Sample::from_raw_parts(bits, 1, 0usize)
+            })
+            .collect();
+        let dataset = Dataset::<usize, usize, Lsb0>::from_samples(samples);
+        let mut encoder = DistributiveThermometer::with_resolution(3);
+        encoder.fit(&dataset);
+        assert_eq!(encoder.quantiles(), &[vec![1, 1, 1]]);
+
+        let encoded = dataset.encode(&encoder);
+        assert_eq!(encoded[0].raw_bits(), bits![0, 0, 0]);
+        assert_eq!(encoded[9].raw_bits(), bits![1, 1, 1]);
+    }
+
+    #[test]
+    fn fit_encoder_errors() {
+        let mut encoder = DistributiveThermometer::with_resolution(2);
+        let sample = dataset()[0].clone();
+        assert!(matches!(
+            encoder.try_encode(sample.clone()),
+            Err(WisardError::EncoderNotFitted),
+        ));
+        assert!(matches!(
+            encoder.try_fit(&Dataset::<usize, usize, Lsb0>::new()),
+            Err(WisardError::EmptyDataset),
+        ));
+
+        encoder.fit(&dataset());
+        let (bits, _, label) = sample.into_raw_parts();
+        let sample = Sample::from_raw_parts(bits[..4].to_bitvec(), 4, label);
+        assert!(matches!(
+            encoder.try_encode(sample),
+            Err(WisardError::InputSizeMismatch { .. }),
+        ));
+    }
+}
@@ -1,12 +1,20 @@
 use bitvec::prelude::*;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::sample::{Label, Sample};
+use crate::{
+    dataset::Dataset,
+    error::WisardResult,
+    sample::{Label, Sample},
+};
 
+mod chain;
+mod fit;
 mod permute;
 mod slice;
 mod therm;
 
+pub use chain::*;
+pub use fit::*;
 pub use permute::*;
 pub use slice::*;
 pub use therm::*;
@@ -27,4 +35,43 @@ where
         self.encode_inplace(&mut sample);
         sample
     }
+
+    /// Encodes the sample in-place, returning an error if the sample cannot
+    /// be encoded. The sample is left unchanged in case of errors.
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        self.encode_inplace(sample);
+        Ok(())
+    }
+
+    /// Consumes the sample and return its encoded version, or an error if
+    /// the sample cannot be encoded.
+    fn try_encode(
+        &self,
+        mut sample: Sample<L, T, O>,
+    ) -> WisardResult<Sample<L, T, O>> {
+        self.try_encode_inplace(&mut sample)?;
+        Ok(sample)
+    }
+}
+
+/// A trait for sample encoders whose parameters are learned from the
+/// samples of a dataset before encoding.
+pub trait FitEncoder<L, T, O>: SampleEncoder<L, T, O>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Learns the encoder parameters from a dataset, returning an error if
+    /// the dataset is empty or its samples have different sizes.
+    fn try_fit(&mut self, dataset: &Dataset<L, T, O>) -> WisardResult<()>;
+
+    /// Learns the encoder parameters from a dataset.
+    fn fit(&mut self, dataset: &Dataset<L, T, O>) {
+        self.try_fit(dataset).unwrap_or_else(|err| panic!("{err}"))
+    }
 }
@@ -1,19 +1,30 @@
 use bitvec::prelude::*;
 use rand::{Rng, RngCore, SeedableRng};
 use rand_xoshiro::Xoshiro256PlusPlus;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::encode::SampleEncoder;
 use crate::sample::{Label, Sample};
 
 /// An encoder that permutes the sample bits according to a given random seed.
-#[derive(Clone)]
+///
+/// The permutation is stored as an index table, where the `i`-th output bit
+/// is gathered from the `table[i]`-th input bit. When created with
+/// [`with_seed_and_size`](#method.with_seed_and_size), the table is computed
+/// once and reused for every sample of that size. Otherwise, it is computed
+/// for each encoded sample.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "<R as SeedableRng>::Seed: Serialize",
+    deserialize = "<R as SeedableRng>::Seed: Deserialize<'de>"
+))]
 pub struct Permute<R = Xoshiro256PlusPlus>
 where
     R: RngCore + SeedableRng,
     <R as SeedableRng>::Seed: Clone,
 {
     seed: <R as SeedableRng>::Seed,
+    table: Vec<usize>,
 }
 
 impl<R> Permute<R>
@@ -30,13 +41,90 @@ where
     /// Creates a new [`Permute`](./structs.Permute.html) encoder instance
     /// using a given `seed` as the permutation seed.
     pub fn with_seed(seed: <R as SeedableRng>::Seed) -> Self {
-        Self { seed }
+        Self {
+            seed,
+            table: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`Permute`](./structs.Permute.html) encoder instance
+    /// using a given `seed` as the permutation seed, precomputing the
+    /// permutation table for samples of `size` bits.
+    pub fn with_seed_and_size(
+        seed: <R as SeedableRng>::Seed,
+        size: usize,
+    ) -> Self {
+        let table = Self::permutation(seed.clone(), size);
+        Self { seed, table }
     }
 
     /// Returns the internal permutation seed.
     pub fn seed(&self) -> &<R as SeedableRng>::Seed {
         &self.seed
     }
+
+    /// Returns the precomputed permutation table, which is empty if the
+    /// encoder was not created for a given sample size.
+    pub fn table(&self) -> &[usize] {
+        &self.table
+    }
+
+    /// Returns the inverse of the precomputed permutation table, such that
+    /// the `table[i]`-th output bit is gathered from the `i`-th input bit.
+    pub fn inverse(&self) -> Vec<usize> {
+        let mut inverse = vec![0; self.table.len()];
+
+        for (i, &j) in self.table.iter().enumerate() {
+            inverse[j] = i;
+        }
+
+        inverse
+    }
+
+    /// Reverts the permutation of a previously encoded sample in-place.
+    pub fn decode_inplace<L, T, O>(&self, sample: &mut Sample<L, T, O>)
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let inverse = match self.table.len() == sample.len() {
+            true => self.inverse(),
+            false => Self::with_seed_and_size(self.seed.clone(), sample.len())
+                .inverse(),
+        };
+        gather(sample, &inverse);
+    }
+
+    /// Consumes a previously encoded sample and returns its original version.
+    pub fn decode<L, T, O>(
+        &self,
+        mut sample: Sample<L, T, O>,
+    ) -> Sample<L, T, O>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.decode_inplace(&mut sample);
+        sample
+    }
+
+    /// Computes the permutation table of `size` elements for a given seed
+    /// using the Fisher-Yates shuffle.
+    fn permutation(seed: <R as SeedableRng>::Seed, size: usize) -> Vec<usize> {
+        let mut rng = R::from_seed(seed);
+        let mut table = (0..size).collect::<Vec<_>>();
+        let m = size.saturating_sub(1);
+
+        for i in 0..m {
+            table.swap(i, rng.gen_range(i..=m));
+        }
+
+        table
+    }
 }
 
 impl<R> Default for Permute<R>
@@ -62,15 +150,29 @@ where
     <R as SeedableRng>::Seed: Clone,
 {
     fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
-        let mut rng = R::from_seed(self.seed.clone());
-        let bits = sample.raw_bits_mut();
-        let m = bits.len() - 1;
-        for i in 0..m {
-            bits.swap(i, rng.gen_range(i..=m));
+        match self.table.len() == sample.len() {
+            true => gather(sample, &self.table),
+            false => {
+                let table = Self::permutation(self.seed.clone(), sample.len());
+                gather(sample, &table);
+            }
         }
     }
 }
 
+/// Replaces the sample bits so that the `i`-th bit is the `table[i]`-th bit.
+fn gather<L, T, O>(sample: &mut Sample<L, T, O>, table: &[usize])
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    let bits = sample.raw_bits();
+    let permuted = table.iter().map(|&i| bits[i]).collect::<BitVec<T, O>>();
+    sample.set_raw_bits(permuted);
+}
+
 #[cfg(test)]
 mod tests {
     use bitvec::prelude::*;
@@ -92,4 +194,24 @@ mod tests {
         assert_eq!(permute.encode(sample_1), sample_1_perm);
         assert_eq!(permute.encode(sample_2), sample_2_perm);
     }
+
+    #[test]
+    fn permute_precomputed_table() {
+        let sample =
+            Sample::from_raw_parts(bitvec![0, 1, 1, 0, 1, 0, 0, 1], 1, 0usize);
+        let seed = 0xBAD_5EED_u32.to_le_bytes().repeat(8).try_into().unwrap();
+        let lazy = <Permute>::with_seed(seed);
+        let precomputed = <Permute>::with_seed_and_size(seed, sample.len());
+        assert!(lazy.table().is_empty());
+        assert_eq!(precomputed.table().len(), sample.len());
+        let encoded = precomputed.encode(sample.clone());
+        assert_eq!(lazy.encode(sample.clone()), encoded);
+        assert_eq!(precomputed.decode(encoded.clone()), sample);
+        assert_eq!(lazy.decode(encoded), sample);
+
+        let inverse = precomputed.inverse();
+        for (i, &j) in precomputed.table().iter().enumerate() {
+            assert_eq!(inverse[j], i);
+        }
+    }
 }
@@ -1,11 +1,12 @@
 use bitvec::{order::BitOrder, store::BitStore, vec::BitVec, view::BitView};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::encode::SampleEncoder;
+use crate::error::{WisardError, WisardResult};
 use crate::sample::{Label, Sample};
 
 /// A logarithmic thermometer encoder.
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Slice {
     start: u8,
     end: u8,
@@ -25,8 +26,36 @@ where
     O: BitOrder,
 {
     fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
+        self.try_encode_inplace(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        let max_bits = usize::BITS as usize;
         let start = self.start as usize;
         let end = self.end as usize;
+
+        if start >= end || end > max_bits {
+            return Err(WisardError::InvalidSlice {
+                start: self.start,
+                end: self.end,
+            });
+        }
+
+        if sample.vsize() == 0 {
+            return Err(WisardError::ZeroValueSize);
+        }
+
+        if sample.vsize() > max_bits {
+            return Err(WisardError::ValueSizeTooLarge {
+                vsize: sample.vsize(),
+                max: max_bits,
+            });
+        }
+
         let size = end - start;
         let out_size = (sample.len() / sample.vsize()) * size;
         let mut bits = BitVec::<T, O>::with_capacity(out_size);
@@ -42,6 +71,7 @@ where
 
         sample.set_raw_bits(bits);
         sample.set_vsize(end - start);
+        Ok(())
     }
 }
 
@@ -102,4 +132,16 @@ mod tests {
         Slice::new(1, 2).encode_inplace(&mut sample);
         assert_eq!(sample, sample_slice);
     }
+
+    #[test]
+    fn slice_invalid_range() {
+        let sample = Sample::from_raw_parts(bitvec![0, 1, 1, 0], 2, 0usize);
+        let err = Slice::new(2, 1).try_encode(sample.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            WisardError::InvalidSlice { start: 2, end: 1 }
+        ));
+        let err = Slice::new(0, 65).try_encode(sample).unwrap_err();
+        assert!(matches!(err, WisardError::InvalidSlice { .. }));
+    }
 }
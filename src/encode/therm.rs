@@ -2,13 +2,45 @@ use bitvec::{
     field::BitField, order::BitOrder, slice::BitSlice, store::BitStore,
     vec::BitVec, view::BitView,
 };
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::encode::SampleEncoder;
+use crate::error::{WisardError, WisardResult};
 use crate::sample::{Label, Sample};
 
+/// The largest value size supported by the thermometer encoders, in bits.
+const MAX_BITS: usize = usize::BITS as usize;
+
+/// Checks if a sample value size can be handled by the thermometer encoders.
+fn check_vsize(vsize: usize) -> WisardResult<()> {
+    if vsize == 0 {
+        return Err(WisardError::ZeroValueSize);
+    }
+
+    if vsize > MAX_BITS {
+        return Err(WisardError::ValueSizeTooLarge {
+            vsize,
+            max: MAX_BITS,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks if a resolution can be handled by the thermometer encoders.
+fn check_resolution(resolution: u8) -> WisardResult<()> {
+    if resolution as usize >= MAX_BITS {
+        return Err(WisardError::ResolutionTooLarge {
+            resolution,
+            max: MAX_BITS - 1,
+        });
+    }
+
+    Ok(())
+}
+
 /// A logarithmic thermometer encoder.
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogThermometer {
     /// The resolution (output size), in bits.
     resolution: u8,
@@ -19,11 +51,20 @@ impl LogThermometer {
     /// with a resolution (output size) of `resolution` bits. The `resolution`
     /// must be a power of two.
     pub fn with_resolution(resolution: u8) -> Self {
-        assert!(
-            resolution.is_power_of_two(),
-            "LogThermometer only supports resolutions that are powers of two",
-        );
-        Self { resolution }
+        Self::try_with_resolution(resolution)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`LogThermometer`](./struct.LogThermometer.html) instance
+    /// with a resolution (output size) of `resolution` bits, returning an
+    /// error if the `resolution` is not a power of two or is too large.
+    pub fn try_with_resolution(resolution: u8) -> WisardResult<Self> {
+        if !resolution.is_power_of_two() {
+            return Err(WisardError::ResolutionNotPowerOfTwo(resolution));
+        }
+
+        check_resolution(resolution)?;
+        Ok(Self { resolution })
     }
 }
 
@@ -35,17 +76,18 @@ where
     O: BitOrder,
 {
     fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
-        let max_bits = std::mem::size_of::<usize>() << 3;
+        self.try_encode_inplace(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        if sample.vsize() > max_bits {
-            panic!(
-                "LogThermometer can only encode values up to {} bits",
-                max_bits,
-            );
-        }
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        check_vsize(sample.vsize())?;
 
         if !sample.vsize().is_power_of_two() {
-            panic!("Sample size must be a power of two");
+            return Err(WisardError::ValueSizeNotPowerOfTwo(sample.vsize()));
         }
 
         let resolution = self.resolution as usize;
@@ -56,7 +98,7 @@ where
             let mut orig_value = 0usize;
             orig_value.view_bits_mut::<O>()[..value.len()]
                 .clone_from_bitslice(value);
-            orig_value = (orig_value + 1).next_power_of_two().ilog2() as usize;
+            orig_value = (usize::BITS - orig_value.leading_zeros()) as usize;
 
             if sample.vsize() < resolution {
                 orig_value *= resolution / sample.vsize();
@@ -71,11 +113,12 @@ where
 
         sample.set_raw_bits(bits);
         sample.set_vsize(resolution);
+        Ok(())
     }
 }
 
 /// A linear thermometer encoder.
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LinearThermometer {
     /// The resolution (output size), in bits.
     resolution: u8,
@@ -85,7 +128,16 @@ impl LinearThermometer {
     /// Creates a new [`LinearThermometer`](./struct.LinearThermometer.html)
     /// instance with a resolution (output size) of `resolution` bits.
     pub fn with_resolution(resolution: u8) -> Self {
-        Self { resolution }
+        Self::try_with_resolution(resolution)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`LinearThermometer`](./struct.LinearThermometer.html)
+    /// instance with a resolution (output size) of `resolution` bits,
+    /// returning an error if the `resolution` is too large.
+    pub fn try_with_resolution(resolution: u8) -> WisardResult<Self> {
+        check_resolution(resolution)?;
+        Ok(Self { resolution })
     }
 }
 
@@ -98,14 +150,15 @@ where
     BitSlice<T, O>: BitField,
 {
     fn encode_inplace(&self, sample: &mut Sample<L, T, O>) {
-        let max_bits = std::mem::size_of::<usize>() << 3;
+        self.try_encode_inplace(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        if sample.vsize() > max_bits {
-            panic!(
-                "LinearThermometer can only encode values up to {} bits",
-                max_bits,
-            );
-        }
+    fn try_encode_inplace(
+        &self,
+        sample: &mut Sample<L, T, O>,
+    ) -> WisardResult<()> {
+        check_vsize(sample.vsize())?;
 
         let resolution = self.resolution as usize;
         let out_size = (sample.len() / sample.vsize()) * resolution;
@@ -115,9 +168,9 @@ where
             let mut bit_value = 0usize;
             bit_value.view_bits_mut::<O>()[..value.len()]
                 .clone_from_bitslice(value);
-            let quant_value = ((resolution + 1) * bit_value
-                + (value.len() >> 1))
-                >> value.len();
+            let quant_value = (((resolution + 1) as u128 * bit_value as u128
+                + (value.len() >> 1) as u128)
+                >> value.len()) as usize;
             let therm_value = (1usize << quant_value) - 1;
             let therm_value = &therm_value.view_bits::<O>()[..resolution];
             bits.extend_from_bitslice(therm_value);
@@ -125,6 +178,7 @@ where
 
         sample.set_raw_bits(bits);
         sample.set_vsize(resolution);
+        Ok(())
     }
 }
 
@@ -315,4 +369,39 @@ mod tests {
         LinearThermometer::with_resolution(4).encode_inplace(&mut sample);
         assert_eq!(sample, sample_therm);
     }
+
+    #[test]
+    fn therm_invalid_vsize() {
+        let sample =
+            Sample::from_raw_parts(bitvec![0, 1, 1, 0, 1, 1], 3, 0usize);
+        let err = LogThermometer::with_resolution(2)
+            .try_encode(sample.clone())
+            .unwrap_err();
+        assert!(matches!(err, WisardError::ValueSizeNotPowerOfTwo(3)));
+
+        let mut sample = sample;
+        sample.set_vsize(0);
+        let err = LinearThermometer::with_resolution(2)
+            .try_encode(sample)
+            .unwrap_err();
+        assert!(matches!(err, WisardError::ZeroValueSize));
+
+        let sample = Sample::from_raw_parts(bitvec![0; 128], 128, 0usize);
+        let err = LinearThermometer::with_resolution(2)
+            .try_encode(sample)
+            .unwrap_err();
+        assert!(matches!(err, WisardError::ValueSizeTooLarge { .. }));
+    }
+
+    #[test]
+    fn therm_invalid_resolution() {
+        assert!(matches!(
+            LogThermometer::try_with_resolution(3),
+            Err(WisardError::ResolutionNotPowerOfTwo(3)),
+        ));
+        assert!(matches!(
+            LinearThermometer::try_with_resolution(64),
+            Err(WisardError::ResolutionTooLarge { .. }),
+        ));
+    }
 }
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::{dataset::DatasetError, model::FormatError};
+
+pub type WisardResult<T> = Result<T, WisardError>;
+
+/// The error type for every fallible operation in the crate.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WisardError {
+    /// An error while reading or writing a dataset.
+    Dataset(DatasetError),
+    /// An error while reading or writing a model file.
+    Format(FormatError),
+    /// A sample label is not known by the model.
+    UnknownLabel(String),
+    /// The model has no labels, so it cannot make predictions.
+    NoLabels,
+    /// A sample does not have the number of bits expected by the model.
+    InputSizeMismatch { expected: usize, found: usize },
+    /// A sample has a value size of zero bits.
+    ZeroValueSize,
+    /// A sample value size is larger than the encoder supports.
+    ValueSizeTooLarge { vsize: usize, max: usize },
+    /// A sample value size is not a power of two.
+    ValueSizeNotPowerOfTwo(usize),
+    /// An encoder resolution is not a power of two.
+    ResolutionNotPowerOfTwo(u8),
+    /// An encoder resolution is larger than the encoder supports.
+    ResolutionTooLarge { resolution: u8, max: usize },
+    /// A slice range is empty or out of the supported bounds.
+    InvalidSlice { start: u8, end: u8 },
+    /// A tuple mapping is invalid or incompatible with the model.
+    InvalidMapping(&'static str),
+    /// An item could not be hashed into a filter address.
+    InvalidAddress(&'static str),
+    /// Two filters, discriminators or models cannot be merged.
+    IncompatibleMerge(&'static str),
+    /// An encoder was fitted with a dataset without samples.
+    EmptyDataset,
+    /// An encoder was used before being fitted with a dataset.
+    EncoderNotFitted,
+}
+
+impl fmt::Display for WisardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dataset(err) => write!(f, "dataset error: {err}"),
+            Self::Format(err) => write!(f, "model format error: {err}"),
+            Self::UnknownLabel(label) => write!(f, "unknown label: {label}"),
+            Self::NoLabels => write!(f, "the model has no labels"),
+            Self::InputSizeMismatch { expected, found } => write!(
+                f,
+                "expected a sample with {expected} bits, found {found} bits",
+            ),
+            Self::ZeroValueSize => write!(f, "sample value size is zero"),
+            Self::ValueSizeTooLarge { vsize, max } => write!(
+                f,
+                "sample value size of {vsize} bits is larger than {max} bits",
+            ),
+            Self::ValueSizeNotPowerOfTwo(vsize) => write!(
+                f,
+                "sample value size of {vsize} bits is not a power of two",
+            ),
+            Self::ResolutionNotPowerOfTwo(resolution) => write!(
+                f,
+                "resolution of {resolution} bits is not a power of two",
+            ),
+            Self::ResolutionTooLarge { resolution, max } => write!(
+                f,
+                "resolution of {resolution} bits is larger than {max} bits",
+            ),
+            Self::InvalidSlice { start, end } => {
+                write!(f, "invalid slice range: {start}..{end}")
+            }
+            Self::InvalidMapping(reason) => {
+                write!(f, "invalid tuple mapping: {reason}")
+            }
+            Self::InvalidAddress(reason) => {
+                write!(f, "invalid filter address: {reason}")
+            }
+            Self::IncompatibleMerge(reason) => {
+                write!(f, "cannot merge: {reason}")
+            }
+            Self::EmptyDataset => write!(f, "the dataset has no samples"),
+            Self::EncoderNotFitted => {
+                write!(f, "the encoder was not fitted with a dataset")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WisardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Dataset(err) => Some(err),
+            Self::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DatasetError> for WisardError {
+    fn from(err: DatasetError) -> Self {
+        Self::Dataset(err)
+    }
+}
+
+impl From<FormatError> for WisardError {
+    fn from(err: FormatError) -> Self {
+        Self::Format(err)
+    }
+}
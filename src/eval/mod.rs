@@ -0,0 +1,388 @@
+use std::{collections::HashMap, fmt};
+
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dataset::Dataset,
+    model::Classifier,
+    sample::{Label, Sample},
+};
+
+mod search;
+
+pub use search::*;
+
+/// A confusion matrix, counting the predictions made for each actual label.
+///
+/// Labels are kept in order of their first appearance, either as an actual
+/// or as a predicted label. Metrics that are undefined for a given label,
+/// such as the precision of a label that was never predicted, are zero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ConfusionMatrix<L: Label> {
+    labels: Vec<L>,
+    counts: HashMap<(L, L), usize>,
+}
+
+impl<L: Label> ConfusionMatrix<L> {
+    /// Creates a new, empty [`ConfusionMatrix`](./struct.ConfusionMatrix.html)
+    /// instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`ConfusionMatrix`](./struct.ConfusionMatrix.html)
+    /// instance from pairs of actual and predicted labels.
+    pub fn from_predictions<I>(predictions: I) -> Self
+    where
+        I: IntoIterator<Item = (L, L)>,
+    {
+        let mut matrix = Self::new();
+
+        for (actual, predicted) in predictions {
+            matrix.add(actual, predicted);
+        }
+
+        matrix
+    }
+
+    /// Creates a new [`ConfusionMatrix`](./struct.ConfusionMatrix.html)
+    /// instance by running a `predict` function, such as the `predict`
+    /// method of a model, over every sample.
+    pub fn from_samples<'a, T, O, I, F>(samples: I, mut predict: F) -> Self
+    where
+        L: 'a,
+        T: BitStore + DeserializeOwned + 'a,
+        T::Mem: Serialize,
+        O: BitOrder + 'a,
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+        F: FnMut(&Sample<L, T, O>) -> L,
+    {
+        Self::from_predictions(
+            samples
+                .into_iter()
+                .map(|sample| (*sample.label(), predict(sample))),
+        )
+    }
+
+    /// Creates a new [`ConfusionMatrix`](./struct.ConfusionMatrix.html)
+    /// instance by running a `predict` function, such as the `predict`
+    /// method of a model, over every sample of a dataset.
+    pub fn from_dataset<T, O, F>(dataset: &Dataset<L, T, O>, predict: F) -> Self
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        F: FnMut(&Sample<L, T, O>) -> L,
+    {
+        Self::from_samples(dataset.iter(), predict)
+    }
+
+    /// Creates a new [`ConfusionMatrix`](./struct.ConfusionMatrix.html)
+    /// instance with the predictions of a
+    /// [`Classifier`](../model/trait.Classifier.html) for every sample.
+    pub fn from_classifier<'a, C, T, O, I>(model: &C, samples: I) -> Self
+    where
+        L: 'a,
+        C: Classifier<L, T, O>,
+        T: BitStore + DeserializeOwned + 'a,
+        T::Mem: Serialize,
+        O: BitOrder + 'a,
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+    {
+        Self::from_samples(samples, |sample| model.predict(sample))
+    }
+
+    /// Adds a prediction to the matrix.
+    pub fn add(&mut self, actual: L, predicted: L) {
+        for label in [actual, predicted] {
+            if !self.labels.contains(&label) {
+                self.labels.push(label);
+            }
+        }
+
+        *self.counts.entry((actual, predicted)).or_default() += 1;
+    }
+
+    /// Returns the labels of the matrix, in order of their first appearance.
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    /// Returns the number of samples of the `actual` label that were
+    /// predicted as the `predicted` label.
+    pub fn count(&self, actual: L, predicted: L) -> usize {
+        self.counts.get(&(actual, predicted)).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of predictions.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Returns the number of correct predictions.
+    pub fn correct(&self) -> usize {
+        self.labels.iter().map(|&l| self.count(l, l)).sum()
+    }
+
+    /// Returns the number of samples whose actual label is `label`.
+    pub fn support(&self, label: L) -> usize {
+        self.labels.iter().map(|&p| self.count(label, p)).sum()
+    }
+
+    /// Returns the number of samples predicted as `label`.
+    pub fn predicted(&self, label: L) -> usize {
+        self.labels.iter().map(|&a| self.count(a, label)).sum()
+    }
+
+    /// Returns the fraction of correct predictions.
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct(), self.total())
+    }
+
+    /// Returns the fraction of the predictions of `label` that are correct.
+    pub fn precision(&self, label: L) -> f64 {
+        ratio(self.count(label, label), self.predicted(label))
+    }
+
+    /// Returns the fraction of the samples of `label` predicted correctly.
+    pub fn recall(&self, label: L) -> f64 {
+        ratio(self.count(label, label), self.support(label))
+    }
+
+    /// Returns the harmonic mean of the precision and recall of `label`.
+    pub fn f1(&self, label: L) -> f64 {
+        f1(self.precision(label), self.recall(label))
+    }
+
+    /// Returns the unweighted mean of the precision of every label.
+    pub fn macro_precision(&self) -> f64 {
+        self.macro_mean(|label| self.precision(label))
+    }
+
+    /// Returns the unweighted mean of the recall of every label.
+    pub fn macro_recall(&self) -> f64 {
+        self.macro_mean(|label| self.recall(label))
+    }
+
+    /// Returns the unweighted mean of the F1 score of every label.
+    pub fn macro_f1(&self) -> f64 {
+        self.macro_mean(|label| self.f1(label))
+    }
+
+    /// Returns the precision computed over the predictions of every label.
+    pub fn micro_precision(&self) -> f64 {
+        let predicted = self.labels.iter().map(|&l| self.predicted(l)).sum();
+        ratio(self.correct(), predicted)
+    }
+
+    /// Returns the recall computed over the samples of every label.
+    pub fn micro_recall(&self) -> f64 {
+        let support = self.labels.iter().map(|&l| self.support(l)).sum();
+        ratio(self.correct(), support)
+    }
+
+    /// Returns the harmonic mean of the micro precision and recall.
+    pub fn micro_f1(&self) -> f64 {
+        f1(self.micro_precision(), self.micro_recall())
+    }
+
+    fn macro_mean<F: Fn(L) -> f64>(&self, metric: F) -> f64 {
+        let sum = self.labels.iter().map(|&l| metric(l)).sum::<f64>();
+        match self.labels.len() {
+            0 => 0.0,
+            len => sum / len as f64,
+        }
+    }
+}
+
+impl<L: Label> Default for ConfusionMatrix<L> {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<L: Label> fmt::Display for ConfusionMatrix<L> {
+    /// Writes a text report with the confusion matrix, where rows are the
+    /// actual labels and columns are the predicted labels, followed by the
+    /// metrics of each label and their averages.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self
+            .labels
+            .iter()
+            .map(|label| format!("{label:?}"))
+            .collect::<Vec<_>>();
+        let width = names
+            .iter()
+            .map(String::len)
+            .chain(self.counts.values().map(|c| c.to_string().len()))
+            .chain(["accuracy".len()])
+            .max()
+            .unwrap_or(0);
+
+        write!(f, "{:>width$}", "")?;
+
+        for name in names.iter() {
+            write!(f, " {name:>width$}")?;
+        }
+
+        writeln!(f)?;
+
+        for (&actual, name) in self.labels.iter().zip(names.iter()) {
+            write!(f, "{name:>width$}")?;
+
+            for &predicted in self.labels.iter() {
+                write!(f, " {:>width$}", self.count(actual, predicted))?;
+            }
+
+            writeln!(f)?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9} {:>9}",
+            "", "precision", "recall", "f1", "support",
+        )?;
+
+        for (&label, name) in self.labels.iter().zip(names.iter()) {
+            writeln!(
+                f,
+                "{name:>width$} {:>9.4} {:>9.4} {:>9.4} {:>9}",
+                self.precision(label),
+                self.recall(label),
+                self.f1(label),
+                self.support(label),
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9.4} {:>9}",
+            "accuracy",
+            "",
+            "",
+            self.accuracy(),
+            self.total(),
+        )?;
+        writeln!(
+            f,
+            "{:>width$} {:>9.4} {:>9.4} {:>9.4} {:>9}",
+            "macro",
+            self.macro_precision(),
+            self.macro_recall(),
+            self.macro_f1(),
+            self.total(),
+        )?;
+        writeln!(
+            f,
+            "{:>width$} {:>9.4} {:>9.4} {:>9.4} {:>9}",
+            "micro",
+            self.micro_precision(),
+            self.micro_recall(),
+            self.micro_f1(),
+            self.total(),
+        )
+    }
+}
+
+/// Returns `num / den`, or zero if `den` is zero.
+fn ratio(num: usize, den: usize) -> f64 {
+    match den {
+        0 => 0.0,
+        _ => num as f64 / den as f64,
+    }
+}
+
+/// Returns the harmonic mean of a precision and a recall.
+fn f1(precision: f64, recall: f64) -> f64 {
+    match precision + recall {
+        sum if sum > 0.0 => 2.0 * precision * recall / sum,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::model::BinaryWisard;
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-9, "{left} != {right}");
+    }
+
+    #[test]
+    fn confusion_matrix_metrics() {
+        let matrix = ConfusionMatrix::from_predictions([
+            (0, 0),
+            (0, 0),
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (2, 2),
+        ]);
+        assert_eq!(matrix.labels(), &[0, 1, 2]);
+        assert_eq!(matrix.count(0, 1), 1);
+        assert_eq!(matrix.total(), 6);
+        assert_eq!(matrix.support(0), 3);
+        assert_close(matrix.accuracy(), 4.0 / 6.0);
+        assert_close(matrix.precision(0), 2.0 / 3.0);
+        assert_close(matrix.recall(0), 2.0 / 3.0);
+        assert_close(matrix.precision(1), 0.5);
+        assert_close(matrix.recall(1), 0.5);
+        assert_close(matrix.f1(2), 1.0);
+        assert_close(matrix.macro_precision(), (2.0 / 3.0 + 0.5 + 1.0) / 3.0);
+        assert_close(matrix.macro_recall(), (2.0 / 3.0 + 0.5 + 1.0) / 3.0);
+        assert_close(matrix.micro_f1(), matrix.accuracy());
+    }
+
+    #[test]
+    fn confusion_matrix_undefined_metrics() {
+        let matrix = ConfusionMatrix::from_predictions([(0, 1), (0, 1)]);
+        assert_close(matrix.precision(0), 0.0);
+        assert_close(matrix.recall(1), 0.0);
+        assert_close(matrix.f1(0), 0.0);
+        assert_close(ConfusionMatrix::<u8>::new().accuracy(), 0.0);
+    }
+
+    #[test]
+    fn confusion_matrix_from_dataset() {
+        let dataset = Dataset::from_samples(
+            [
+                (bitvec![1, 1, 1, 1, 0, 0, 0, 0], 0usize),
+                (bitvec![1, 1, 1, 0, 0, 0, 0, 0], 0),
+                (bitvec![0, 0, 0, 0, 1, 1, 1, 1], 1),
+                (bitvec![0, 0, 0, 1, 1, 1, 1, 1], 1),
+            ]
+            .into_iter()
+            .map(|(bits, label)| Sample::from_raw_parts(bits, 1, label))
+            .collect(),
+        );
+
+        let labels = HashSet::from_iter([0usize, 1]);
+        let mut model = BinaryWisard::with_seed(8, 2, labels, [4u8; 32]);
+
+        for sample in dataset.iter() {
+            model.fit(sample);
+        }
+
+        let matrix =
+            ConfusionMatrix::from_dataset(&dataset, |s| model.predict(s));
+        assert_eq!(matrix.total(), 4);
+        assert_close(matrix.accuracy(), 1.0);
+
+        let other = ConfusionMatrix::from_classifier(&model, dataset.iter());
+        assert_eq!(other.correct(), matrix.correct());
+
+        let report = matrix.to_string();
+        assert!(report.contains("precision"));
+        assert!(report.contains("accuracy"));
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::HashSet;
+
+use bitvec::prelude::*;
+use rand::{seq::index, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dataset::Dataset,
+    encode::SampleEncoder,
+    error::WisardResult,
+    eval::ConfusionMatrix,
+    model::Classifier,
+    sample::{Label, Sample},
+    util::map_batch,
+};
+
+/// A set of common WiSARD hyperparameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Hyperparams {
+    /// The address size of the RAMs.
+    pub addr_size: usize,
+    /// The resolution of the thermometer encoder.
+    pub resolution: u8,
+    /// The number of bits of each RAM counter.
+    pub count_size: usize,
+}
+
+/// A grid of candidate values for each of the
+/// [`Hyperparams`](./struct.Hyperparams.html), used for grid or random
+/// searches.
+///
+/// Every hyperparameter has a single candidate by default: an address size
+/// of 8, a resolution of 8 and a counter size of 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParamGrid {
+    addr_sizes: Vec<usize>,
+    resolutions: Vec<u8>,
+    count_sizes: Vec<usize>,
+}
+
+impl ParamGrid {
+    /// Creates a new [`ParamGrid`](./struct.ParamGrid.html) instance with the
+    /// default candidates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the candidate address sizes.
+    pub fn with_addr_sizes<I>(mut self, addr_sizes: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.addr_sizes = candidates(addr_sizes);
+        self
+    }
+
+    /// Sets the candidate thermometer resolutions.
+    pub fn with_resolutions<I>(mut self, resolutions: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.resolutions = candidates(resolutions);
+        self
+    }
+
+    /// Sets the candidate counter sizes.
+    pub fn with_count_sizes<I>(mut self, count_sizes: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.count_sizes = candidates(count_sizes);
+        self
+    }
+
+    /// Returns the number of configurations in the grid.
+    pub fn len(&self) -> usize {
+        self.addr_sizes.len()
+            * self.resolutions.len()
+            * self.count_sizes.len()
+    }
+
+    /// Returns `true` if the grid has no configurations, which never happens
+    /// since every hyperparameter has at least one candidate.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the configuration at a given `index` of the grid, or `None`
+    /// if the index is out of bounds.
+    ///
+    /// Configurations are ordered by address size, then resolution and
+    /// finally counter size.
+    pub fn get(&self, index: usize) -> Option<Hyperparams> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let count_size = index % self.count_sizes.len();
+        let index = index / self.count_sizes.len();
+        let resolution = index % self.resolutions.len();
+        let addr_size = index / self.resolutions.len();
+
+        Some(Hyperparams {
+            addr_size: self.addr_sizes[addr_size],
+            resolution: self.resolutions[resolution],
+            count_size: self.count_sizes[count_size],
+        })
+    }
+
+    /// Returns every configuration of the grid, for a grid search.
+    pub fn configs(&self) -> Vec<Hyperparams> {
+        (0..self.len()).filter_map(|i| self.get(i)).collect()
+    }
+
+    /// Returns up to `n` distinct configurations of the grid, chosen at
+    /// random using a given `seed`, for a random search.
+    pub fn sample(&self, n: usize, seed: [u8; 32]) -> Vec<Hyperparams> {
+        let mut rng = Xoshiro256PlusPlus::from_seed(seed);
+        let amount = n.min(self.len());
+        index::sample(&mut rng, self.len(), amount)
+            .into_iter()
+            .filter_map(|i| self.get(i))
+            .collect()
+    }
+}
+
+impl Default for ParamGrid {
+    fn default() -> Self {
+        Self {
+            addr_sizes: vec![8],
+            resolutions: vec![8],
+            count_sizes: vec![1],
+        }
+    }
+}
+
+/// Collects the candidates of a hyperparameter, which must not be empty.
+fn candidates<V, I: IntoIterator<Item = V>>(values: I) -> Vec<V> {
+    let values = values.into_iter().collect::<Vec<_>>();
+    assert!(!values.is_empty(), "hyperparameters require a candidate");
+    values
+}
+
+/// The cross-validation accuracies of a hyperparameter configuration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchResult<P> {
+    params: P,
+    accuracies: Vec<f64>,
+}
+
+impl<P> SearchResult<P> {
+    /// Returns the evaluated configuration.
+    pub fn params(&self) -> &P {
+        &self.params
+    }
+
+    /// Returns the validation accuracy of each fold.
+    pub fn accuracies(&self) -> &[f64] {
+        &self.accuracies
+    }
+
+    /// Returns the mean accuracy over the folds.
+    pub fn mean(&self) -> f64 {
+        if self.accuracies.is_empty() {
+            return 0.0;
+        }
+
+        self.accuracies.iter().sum::<f64>() / self.accuracies.len() as f64
+    }
+
+    /// Returns the population standard deviation of the accuracy over the
+    /// folds.
+    pub fn std_dev(&self) -> f64 {
+        if self.accuracies.is_empty() {
+            return 0.0;
+        }
+
+        let mean = self.mean();
+        let variance = self
+            .accuracies
+            .iter()
+            .map(|accuracy| (accuracy - mean).powi(2))
+            .sum::<f64>()
+            / self.accuracies.len() as f64;
+        variance.sqrt()
+    }
+}
+
+/// A k-fold cross-validation harness for hyperparameter searches.
+///
+/// Folds are stratified by label and generated from a given seed, so every
+/// configuration is evaluated on the same folds. With the `rayon` feature
+/// enabled, configurations are evaluated in parallel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrossValidation {
+    folds: usize,
+    seed: [u8; 32],
+}
+
+impl CrossValidation {
+    /// Creates a new [`CrossValidation`](./struct.CrossValidation.html)
+    /// instance with a given number of `folds`, which must be at least two,
+    /// and a `seed` for the fold generation.
+    pub fn new(folds: usize, seed: [u8; 32]) -> Self {
+        assert!(folds > 1, "cross-validation requires at least two folds");
+        Self { folds, seed }
+    }
+
+    /// Returns the number of folds.
+    pub fn folds(&self) -> usize {
+        self.folds
+    }
+
+    /// Returns the seed used to generate the folds.
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Evaluates every configuration of `params`, returning their results
+    /// in the same order.
+    ///
+    /// For each configuration, the dataset is encoded with the encoder built
+    /// by `encoder`, and a new model built by `model` is trained and
+    /// validated on every fold. The model factory also receives the encoded
+    /// input size and the dataset labels.
+    pub fn search<P, L, T, O, E, C, FE, FM>(
+        &self,
+        dataset: &Dataset<L, T, O>,
+        params: &[P],
+        encoder: FE,
+        model: FM,
+    ) -> Vec<SearchResult<P>>
+    where
+        P: Clone + Send + Sync,
+        L: Label + Send + Sync,
+        T: BitStore + Clone + DeserializeOwned + Send + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Send + Sync,
+        E: SampleEncoder<L, T, O>,
+        C: Classifier<L, T, O>,
+        FE: Fn(&P) -> E + Send + Sync,
+        FM: Fn(&P, usize, HashSet<L>) -> C + Send + Sync,
+    {
+        self.try_search(dataset, params, encoder, model)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Evaluates every configuration of `params`, returning their results
+    /// in the same order, or the first error raised while encoding, training
+    /// or validating.
+    pub fn try_search<P, L, T, O, E, C, FE, FM>(
+        &self,
+        dataset: &Dataset<L, T, O>,
+        params: &[P],
+        encoder: FE,
+        model: FM,
+    ) -> WisardResult<Vec<SearchResult<P>>>
+    where
+        P: Clone + Send + Sync,
+        L: Label + Send + Sync,
+        T: BitStore + Clone + DeserializeOwned + Send + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Send + Sync,
+        E: SampleEncoder<L, T, O>,
+        C: Classifier<L, T, O>,
+        FE: Fn(&P) -> E + Send + Sync,
+        FM: Fn(&P, usize, HashSet<L>) -> C + Send + Sync,
+    {
+        map_batch(params, |params| {
+            let encoder = encoder(params);
+            let samples = dataset
+                .iter()
+                .map(|sample| encoder.try_encode(sample.clone()))
+                .collect::<WisardResult<Vec<_>>>()?;
+            let encoded = Dataset::from_samples(samples);
+            let accuracies = self
+                .try_accuracies(&encoded, |size, labels| {
+                    model(params, size, labels)
+                })?;
+
+            Ok(SearchResult {
+                params: params.clone(),
+                accuracies,
+            })
+        })
+    }
+
+    /// Returns the validation accuracy of each fold, training a new model
+    /// built by `model` on every fold. The model factory receives the input
+    /// size and the dataset labels.
+    pub fn accuracies<L, T, O, C, F>(
+        &self,
+        dataset: &Dataset<L, T, O>,
+        model: F,
+    ) -> Vec<f64>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        C: Classifier<L, T, O>,
+        F: Fn(usize, HashSet<L>) -> C,
+    {
+        self.try_accuracies(dataset, model)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the validation accuracy of each fold, or the first error
+    /// raised while training or validating.
+    pub fn try_accuracies<L, T, O, C, F>(
+        &self,
+        dataset: &Dataset<L, T, O>,
+        model: F,
+    ) -> WisardResult<Vec<f64>>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+        C: Classifier<L, T, O>,
+        F: Fn(usize, HashSet<L>) -> C,
+    {
+        let input_size = dataset.iter().next().map(Sample::len).unwrap_or(0);
+        let labels = dataset.labels();
+
+        dataset
+            .stratified_k_fold(self.folds, self.seed)
+            .map(|(train, validation)| {
+                let mut model = model(input_size, labels.clone());
+                model.try_fit_iter(train.iter())?;
+                let predictions = model.try_predict_iter(validation.iter())?;
+                let matrix = ConfusionMatrix::from_predictions(
+                    validation.iter().map(|s| *s.label()).zip(predictions),
+                );
+                Ok(matrix.accuracy())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::LinearThermometer, model::CountingWisard};
+
+    fn dataset() -> Dataset<usize, usize, Lsb0> {
+        let samples = (0..24usize)
+            .map(|i| {
+                let label = i % 2;
+                let value = label * 12 + i % 4;
+                let mut bits = BitVec::new();
+                bits.extend_from_bitslice(&value.view_bits::<Lsb0>()[..5]);
+                bits.extend_from_bitslice(&(i % 3).view_bits::<Lsb0>()[..5]);
+                Sample::from_raw_parts(bits, 5, label)
+            })
+            .collect();
+        Dataset::from_samples(samples)
+    }
+
+    #[test]
+    fn param_grid_configs() {
+        let grid = ParamGrid::new()
+            .with_addr_sizes([2, 4])
+            .with_resolutions([4, 8, 16]);
+        assert_eq!(grid.len(), 6);
+
+        let configs = grid.configs();
+        assert_eq!(configs.len(), 6);
+        assert_eq!(configs[0].addr_size, 2);
+        assert_eq!(configs[0].resolution, 4);
+        assert_eq!(configs[4].addr_size, 4);
+        assert_eq!(configs[4].resolution, 8);
+        assert_eq!(configs[4].count_size, 1);
+        assert!(grid.get(6).is_none());
+
+        let sampled = grid.sample(4, [1; 32]);
+        assert_eq!(sampled.len(), 4);
+        assert_eq!(sampled, grid.sample(4, [1; 32]));
+        assert!(sampled.iter().all(|params| configs.contains(params)));
+        assert_eq!(grid.sample(10, [1; 32]).len(), 6);
+    }
+
+    #[test]
+    fn search_result_stats() {
+        let result = SearchResult {
+            params: (),
+            accuracies: vec![0.5, 1.0, 0.75, 0.75],
+        };
+        assert!((result.mean() - 0.75).abs() < 1e-9);
+        assert!((result.std_dev() - 0.125f64.sqrt() / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cross_validation_search() {
+        let dataset = dataset();
+        let grid = ParamGrid::new()
+            .with_addr_sizes([2, 4])
+            .with_resolutions([4, 8])
+            .with_count_sizes([2]);
+        let cv = CrossValidation::new(3, [2; 32]);
+        let search = || {
+            cv.search(
+                &dataset,
+                &grid.configs(),
+                |params| LinearThermometer::with_resolution(params.resolution),
+                |params, input_size, labels| {
+                    CountingWisard::with_seed(
+                        input_size,
+                        params.addr_size,
+                        params.count_size,
+                        labels,
+                        [3; 32],
+                    )
+                },
+            )
+        };
+
+        let results = search();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[1].params(), &grid.configs()[1]);
+
+        for (result, again) in results.iter().zip(search()) {
+            assert_eq!(result.accuracies().len(), 3);
+            assert_eq!(result.accuracies(), again.accuracies());
+            assert!((0.0..=1.0).contains(&result.mean()));
+        }
+
+        assert!(results.iter().any(|result| result.mean() > 0.9));
+    }
+}
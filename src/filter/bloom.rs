@@ -5,7 +5,12 @@ use std::{
 
 use bloom::{CountingBloomFilter, ASMS};
 
-use crate::filter::{BuildFilter, CountingFilter, Filter};
+use crate::{
+    error::{WisardError, WisardResult},
+    filter::{
+        BuildFilter, CountingFilter, DecrementFilter, Filter, MergeFilter,
+    },
+};
 
 /// A Filter structure based on Bloom filters.
 pub struct BloomFilter<R, S>
@@ -76,6 +81,41 @@ where
     }
 }
 
+impl<R, S> DecrementFilter for BloomFilter<R, S>
+where
+    R: BuildHasher,
+    S: BuildHasher,
+{
+    /// Counters of a Bloom filter are shared between items, so excluding an
+    /// item whose counters saturated on inclusion may affect other items.
+    fn exclude<T: Hash>(&mut self, item: &T) -> bool {
+        self.bloom.remove(item) > 0
+    }
+}
+
+impl<R, S> MergeFilter for BloomFilter<R, S>
+where
+    R: BuildHasher,
+    S: BuildHasher,
+{
+    /// Bloom filters never merge, as their counters are private to the
+    /// underlying filter and cannot be added together.
+    fn can_merge(&self, _other: &Self) -> bool {
+        false
+    }
+
+    /// Returns an error, as the counters of Bloom filters cannot be added.
+    ///
+    /// Including every address of `other` as many times as its estimated
+    /// count would turn its false positives into actual counts, inflating
+    /// the counters on every merge.
+    fn try_merge(&mut self, _other: &Self) -> WisardResult<()> {
+        Err(WisardError::IncompatibleMerge(
+            "Bloom filter counters cannot be added",
+        ))
+    }
+}
+
 /// A builder for [`BloomFilter`](./struct.BloomFilter.html).
 #[derive(Copy, Clone, Debug)]
 pub struct BloomFilterBuilder<R, S>
@@ -157,4 +197,38 @@ mod tests {
         assert_eq!(filter.counter(&value), Some(2));
         assert!(filter.contains(&value));
     }
+
+    #[test]
+    fn bloom_filter_exclude() {
+        let hasher_one = RandomState::new();
+        let hasher_two = RandomState::new();
+        let builder = BloomFilterBuilder::with_rate_and_hashers(
+            4, 4, 0, 0.01, hasher_one, hasher_two,
+        );
+        let mut filter = builder.build_filter();
+        filter.include(&3usize);
+        filter.include(&3usize);
+        assert!(filter.exclude(&3usize));
+        assert_eq!(filter.counter(&3usize), Some(1));
+        assert!(filter.exclude(&3usize));
+        assert!(!filter.exclude(&3usize));
+        assert_eq!(filter.counter(&3usize), Some(0));
+        assert!(!filter.contains(&3usize));
+    }
+
+    #[test]
+    fn bloom_filter_merge() {
+        let hasher_one = RandomState::new();
+        let hasher_two = RandomState::new();
+        let builder = BloomFilterBuilder::with_rate_and_hashers(
+            3, 2, 0, 0.01, hasher_one, hasher_two,
+        );
+        let mut filter = builder.build_filter();
+        let mut other = builder.build_filter();
+        other.include(&1usize);
+        assert!(!filter.can_merge(&other));
+        let err = filter.try_merge(&other).unwrap_err();
+        assert!(matches!(err, WisardError::IncompatibleMerge(_)));
+        assert_eq!(filter.counter(&1usize), Some(0));
+    }
 }
@@ -1,20 +1,25 @@
-use std::{
-    fmt::Debug,
-    hash::{Hash, Hasher},
-};
+use std::{fmt::Debug, hash::Hash, ops::Range};
 
-use bitvec::{bitvec, order::Lsb0, vec::BitVec, view::BitView};
+use bitvec::{
+    bitvec, order::Lsb0, slice::BitSlice, vec::BitVec, view::BitView,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    filter::{BuildFilter, CountingFilter, Filter},
-    util::{Counter, RawIntHasher},
+    error::{WisardError, WisardResult},
+    filter::{
+        BuildFilter, CountingFilter, DecrementFilter, Filter, MergeFilter,
+    },
+    util::{raw_index, Counter},
 };
 
 /// A Filter structure based on dense, integer-aligned lookup tables (LUTs).
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct LUTFilter<C: Counter = u8> {
     addr_size: usize,
+    #[serde(bound = "")]
     threshold: C,
+    #[serde(bound = "")]
     lut: Vec<C>,
 }
 
@@ -37,41 +42,90 @@ impl<C: Counter> LUTFilter<C> {
 
 impl<C: Counter> Filter for LUTFilter<C> {
     fn include<T: Hash>(&mut self, item: &T) -> bool {
-        let mut hasher = RawIntHasher::default();
-        item.hash(&mut hasher);
-        let index = hasher.finish() as usize;
-        self.lut
+        self.try_include(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.try_contains(item)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_include<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
+        let index = raw_index(item)?;
+        Ok(self
+            .lut
             .get_mut(index)
             .map(|count| {
                 *count = count.saturating_add(C::one());
             })
-            .is_some()
+            .is_some())
     }
 
-    fn contains<T: Hash>(&self, item: &T) -> bool {
-        let mut hasher = RawIntHasher::default();
-        item.hash(&mut hasher);
-        let index = hasher.finish() as usize;
-        self.lut
+    fn try_contains<T: Hash>(&self, item: &T) -> WisardResult<bool> {
+        let index = raw_index(item)?;
+        Ok(self
+            .lut
             .get(index)
             .map(|count| *count > self.threshold)
-            .unwrap_or(false)
+            .unwrap_or(false))
     }
 }
 
 impl<C: Counter> CountingFilter for LUTFilter<C> {
     fn counter<T: Hash>(&self, item: &T) -> Option<usize> {
-        let mut hasher = RawIntHasher::default();
-        item.hash(&mut hasher);
-        let index = hasher.finish() as usize;
-        self.lut.get(index).map(|v| (*v).into())
+        self.try_counter(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_counter<T: Hash>(&self, item: &T) -> WisardResult<Option<usize>> {
+        let index = raw_index(item)?;
+        Ok(self.lut.get(index).map(|v| (*v).into()))
+    }
+}
+
+impl<C: Counter> DecrementFilter for LUTFilter<C> {
+    fn exclude<T: Hash>(&mut self, item: &T) -> bool {
+        self.try_exclude(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_exclude<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
+        let index = raw_index(item)?;
+        Ok(self
+            .lut
+            .get_mut(index)
+            .map(|count| {
+                *count = count.saturating_sub(C::one());
+            })
+            .is_some())
+    }
+}
+
+impl<C: Counter> MergeFilter for LUTFilter<C> {
+    fn can_merge(&self, other: &Self) -> bool {
+        self.addr_size == other.addr_size && self.threshold == other.threshold
+    }
+
+    /// Merges the members of `other` by adding the counters of both filters,
+    /// saturating at the counter bounds.
+    fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        if !self.can_merge(other) {
+            return Err(WisardError::IncompatibleMerge(
+                "filters have different parameters",
+            ));
+        }
+
+        for (count, other) in self.lut.iter_mut().zip(other.lut.iter()) {
+            *count = count.saturating_add(*other);
+        }
+
+        Ok(())
     }
 }
 
 /// A builder for [`LUTFilter`](./struct.LUTFilter.html).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct LUTFilterBuilder<C: Counter = u8> {
     addr_size: usize,
+    #[serde(bound = "")]
     threshold: C,
 }
 
@@ -92,7 +146,7 @@ impl<C: Counter> BuildFilter for LUTFilterBuilder<C> {
 }
 
 /// A Filter structure based on dense, bit-packed lookup tables (LUTs).
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct PackedLUTFilter {
     addr_size: usize,
     count_size: usize,
@@ -117,17 +171,76 @@ impl PackedLUTFilter {
             lut: bitvec![usize, Lsb0; 0; count_size << addr_size],
         }
     }
+
+    /// Creates a [`PackedLUTFilter`](./struct.PackedLUTFilter.html) instance
+    /// from its raw parts.
+    pub(crate) fn from_raw_parts(
+        addr_size: usize,
+        count_size: usize,
+        threshold: usize,
+        lut: BitVec,
+    ) -> Self {
+        Self {
+            addr_size,
+            count_size,
+            threshold,
+            lut,
+        }
+    }
+
+    /// Returns the address size of the filter.
+    pub fn addr_size(&self) -> usize {
+        self.addr_size
+    }
+
+    /// Returns the number of bits of each counter.
+    pub fn count_size(&self) -> usize {
+        self.count_size
+    }
+
+    /// Returns the membership threshold of the filter.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns a slice over the raw lookup table bits.
+    pub(crate) fn raw_lut(&self) -> &BitSlice {
+        &self.lut
+    }
+}
+
+impl PackedLUTFilter {
+    /// Returns the bit range of the counter addressed by `item`.
+    fn counter_range<T: Hash>(
+        &self,
+        item: &T,
+    ) -> WisardResult<Option<Range<usize>>> {
+        let index = raw_index(item)?;
+        Ok(index
+            .checked_mul(self.count_size)
+            .and_then(|start| {
+                start.checked_add(self.count_size).map(|end| start..end)
+            })
+            .filter(|range| range.end <= self.lut.len()))
+    }
 }
 
 impl Filter for PackedLUTFilter {
     fn include<T: Hash>(&mut self, item: &T) -> bool {
-        let mut hasher = RawIntHasher::default();
-        item.hash(&mut hasher);
+        self.try_include(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.try_contains(item)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_include<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
         let max_value = (1 << self.count_size) - 1;
-        let index = self.count_size * hasher.finish() as usize;
-        self.lut
-            .get_mut(index..index + self.count_size)
-            .map(|count| {
+        let range = self.counter_range(item)?;
+        Ok(range
+            .map(|range| {
+                let count = &mut self.lut[range];
                 let mut value = 0usize;
                 value.view_bits_mut::<Lsb0>()[..self.count_size]
                     .clone_from_bitslice(count);
@@ -136,32 +249,103 @@ impl Filter for PackedLUTFilter {
                     &value.view_bits::<Lsb0>()[..self.count_size],
                 );
             })
-            .is_some()
+            .is_some())
     }
 
-    fn contains<T: Hash>(&self, item: &T) -> bool {
-        self.counter(item)
+    fn try_contains<T: Hash>(&self, item: &T) -> WisardResult<bool> {
+        Ok(self
+            .try_counter(item)?
             .map(|count| count > self.threshold)
-            .unwrap_or(false)
+            .unwrap_or(false))
     }
 }
 
 impl CountingFilter for PackedLUTFilter {
     fn counter<T: Hash>(&self, item: &T) -> Option<usize> {
-        let mut hasher = RawIntHasher::default();
-        item.hash(&mut hasher);
-        let index = self.count_size * hasher.finish() as usize;
-        self.lut.get(index..index + self.count_size).map(|count| {
+        self.try_counter(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_counter<T: Hash>(&self, item: &T) -> WisardResult<Option<usize>> {
+        let range = self.counter_range(item)?;
+        Ok(range.map(|range| {
             let mut value = 0usize;
             value.view_bits_mut::<Lsb0>()[..self.count_size]
-                .clone_from_bitslice(count);
+                .clone_from_bitslice(&self.lut[range]);
             value
-        })
+        }))
+    }
+}
+
+impl DecrementFilter for PackedLUTFilter {
+    fn exclude<T: Hash>(&mut self, item: &T) -> bool {
+        self.try_exclude(item).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_exclude<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
+        let range = self.counter_range(item)?;
+        Ok(range
+            .map(|range| {
+                let count = &mut self.lut[range];
+                let mut value = 0usize;
+                value.view_bits_mut::<Lsb0>()[..self.count_size]
+                    .clone_from_bitslice(count);
+                value = value.saturating_sub(1);
+                count.clone_from_bitslice(
+                    &value.view_bits::<Lsb0>()[..self.count_size],
+                );
+            })
+            .is_some())
+    }
+}
+
+impl MergeFilter for PackedLUTFilter {
+    fn can_merge(&self, other: &Self) -> bool {
+        self.addr_size == other.addr_size
+            && self.count_size == other.count_size
+            && self.threshold == other.threshold
+            && self.lut.len() == other.lut.len()
+    }
+
+    /// Merges the members of `other` by adding the counters of both filters,
+    /// saturating at the counter bounds. Binary filters (with single-bit
+    /// counters) are merged with a bitwise OR.
+    fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        if !self.can_merge(other) {
+            return Err(WisardError::IncompatibleMerge(
+                "filters have different parameters",
+            ));
+        }
+
+        if self.count_size == 1 {
+            self.lut |= other.lut.as_bitslice();
+            return Ok(());
+        }
+
+        let max_value = (1 << self.count_size) - 1;
+        let counters = self
+            .lut
+            .chunks_mut(self.count_size)
+            .zip(other.lut.chunks(self.count_size));
+
+        for (count, other) in counters {
+            let mut value = 0usize;
+            let mut other_value = 0usize;
+            value.view_bits_mut::<Lsb0>()[..self.count_size]
+                .clone_from_bitslice(count);
+            other_value.view_bits_mut::<Lsb0>()[..self.count_size]
+                .clone_from_bitslice(other);
+            value = max_value.min(value + other_value);
+            count.clone_from_bitslice(
+                &value.view_bits::<Lsb0>()[..self.count_size],
+            );
+        }
+
+        Ok(())
     }
 }
 
 /// A builder for [`PackedLUTFilter`](./struct.PackedLUTFilter.html).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct PackedLUTFilterBuilder {
     addr_size: usize,
     count_size: usize,
@@ -218,4 +402,126 @@ mod tests {
         assert_eq!(filter.counter(&value), Some(2));
         assert!(filter.contains(&value));
     }
+
+    #[test]
+    fn lut_filter_exclude() {
+        let builder = LUTFilterBuilder::new(1, 0u8);
+        let mut filter = builder.build_filter();
+        filter.include(&0usize);
+        filter.include(&0usize);
+        filter.include(&1usize);
+        assert!(filter.exclude(&0usize));
+        assert_eq!(filter.counter(&0usize), Some(1));
+        assert_eq!(filter.counter(&1usize), Some(1));
+        filter.exclude(&0usize);
+        filter.exclude(&0usize);
+        assert_eq!(filter.counter(&0usize), Some(0));
+        assert!(!filter.exclude(&2usize));
+    }
+
+    #[test]
+    fn packed_lut_filter_exclude() {
+        let builder = PackedLUTFilterBuilder::new(1, 2, 0);
+        let mut filter = builder.build_filter();
+        filter.include(&0usize);
+        filter.include(&0usize);
+        filter.include(&1usize);
+        assert!(filter.exclude(&0usize));
+        assert_eq!(filter.counter(&0usize), Some(1));
+        assert_eq!(filter.counter(&1usize), Some(1));
+        filter.exclude(&0usize);
+        filter.exclude(&0usize);
+        assert_eq!(filter.counter(&0usize), Some(0));
+        assert!(!filter.exclude(&2usize));
+    }
+
+    #[test]
+    fn lut_filter_merge() {
+        let builder = LUTFilterBuilder::new(1, 0u8);
+        let mut filter = builder.build_filter();
+        let mut other = builder.build_filter();
+        filter.include(&0usize);
+        other.include(&0usize);
+        other.include(&1usize);
+        filter.merge(&other);
+        assert_eq!(filter.counter(&0usize), Some(2));
+        assert_eq!(filter.counter(&1usize), Some(1));
+        let incompatible = LUTFilterBuilder::new(2, 0u8).build_filter();
+        assert!(filter.try_merge(&incompatible).is_err());
+    }
+
+    #[test]
+    fn packed_lut_filter_merge() {
+        let builder = PackedLUTFilterBuilder::new(1, 2, 0);
+        let mut filter = builder.build_filter();
+        let mut other = builder.build_filter();
+        filter.include(&0usize);
+        filter.include(&0usize);
+        other.include(&0usize);
+        other.include(&0usize);
+        other.include(&1usize);
+        filter.merge(&other);
+        assert_eq!(filter.counter(&0usize), Some(3));
+        assert_eq!(filter.counter(&1usize), Some(1));
+
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        let mut filter = builder.build_filter();
+        let mut other = builder.build_filter();
+        filter.include(&0usize);
+        other.include(&0usize);
+        other.include(&2usize);
+        filter.merge(&other);
+        assert_eq!(filter.counter(&0usize), Some(1));
+        assert_eq!(filter.counter(&1usize), Some(0));
+        assert_eq!(filter.counter(&2usize), Some(1));
+        let incompatible = PackedLUTFilterBuilder::new(2, 2, 0).build_filter();
+        assert!(filter.try_merge(&incompatible).is_err());
+    }
+
+    #[test]
+    fn lut_filter_serde_round_trip() {
+        let mut filter = LUTFilterBuilder::new(2, 0u16).build_filter();
+        filter.include(&1usize);
+        filter.include(&3usize);
+        filter.include(&3usize);
+        let bytes = bincode::serialize(&filter).unwrap();
+        let loaded: LUTFilter<u16> = bincode::deserialize(&bytes).unwrap();
+        assert!(loaded == filter);
+        assert_eq!(loaded.counter(&3usize), Some(2));
+    }
+
+    #[test]
+    fn packed_lut_filter_serde_round_trip() {
+        let mut filter = PackedLUTFilterBuilder::new(2, 3, 1).build_filter();
+        filter.include(&1usize);
+        filter.include(&3usize);
+        filter.include(&3usize);
+        let bytes = bincode::serialize(&filter).unwrap();
+        let loaded: PackedLUTFilter = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded, filter);
+        assert_eq!(loaded.counter(&3usize), Some(2));
+    }
+
+    #[test]
+    fn lut_filter_invalid_address() {
+        let mut filter = LUTFilterBuilder::new(2, 0u8).build_filter();
+        assert!(filter.try_include(&"a").is_err());
+        assert!(filter.try_contains(&(0u8, 1u8)).is_err());
+        assert!(!filter.try_include(&4usize).unwrap());
+        assert_eq!(filter.try_counter(&3usize).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn packed_lut_filter_invalid_address() {
+        let mut filter = PackedLUTFilterBuilder::new(2, 2, 0).build_filter();
+        assert!(filter.try_include(&"a").is_err());
+        assert!(filter.try_contains(&(0u8, 1u8)).is_err());
+        assert!(!filter.try_include(&4usize).unwrap());
+        assert_eq!(filter.try_counter(&usize::MAX).unwrap(), None);
+        let mut filter = PackedLUTFilterBuilder::new(2, 1, 0).build_filter();
+        assert!(!filter.try_include(&usize::MAX).unwrap());
+        assert!(!filter.try_contains(&usize::MAX).unwrap());
+        assert!(!filter.try_exclude(&usize::MAX).unwrap());
+        assert_eq!(filter.try_counter(&usize::MAX).unwrap(), None);
+    }
 }
@@ -1,5 +1,7 @@
 use std::hash::Hash;
 
+use crate::error::WisardResult;
+
 mod bloom;
 mod lut;
 
@@ -12,12 +14,64 @@ pub trait Filter {
     fn include<T: Hash>(&mut self, item: &T) -> bool;
     /// Checks the membership of an item.
     fn contains<T: Hash>(&self, item: &T) -> bool;
+
+    /// Includes an item as a member, returning an error if the item cannot
+    /// be addressed by the filter.
+    fn try_include<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
+        Ok(self.include(item))
+    }
+
+    /// Checks the membership of an item, returning an error if the item
+    /// cannot be addressed by the filter.
+    fn try_contains<T: Hash>(&self, item: &T) -> WisardResult<bool> {
+        Ok(self.contains(item))
+    }
 }
 
 /// A trait for set membership filters that uses counters.
 pub trait CountingFilter: Filter {
     /// Returns the number of times a member was included.
     fn counter<T: Hash>(&self, item: &T) -> Option<usize>;
+
+    /// Returns the number of times a member was included, returning an error
+    /// if the item cannot be addressed by the filter.
+    fn try_counter<T: Hash>(&self, item: &T) -> WisardResult<Option<usize>> {
+        Ok(self.counter(item))
+    }
+}
+
+/// A trait for counting filters that also support removing members.
+pub trait DecrementFilter: CountingFilter {
+    /// Excludes an item previously included, decrementing its counter.
+    ///
+    /// Counters saturate at zero, so excluding an item that was never
+    /// included leaves the filter unchanged.
+    fn exclude<T: Hash>(&mut self, item: &T) -> bool;
+
+    /// Excludes an item previously included, returning an error if the item
+    /// cannot be addressed by the filter.
+    fn try_exclude<T: Hash>(&mut self, item: &T) -> WisardResult<bool> {
+        Ok(self.exclude(item))
+    }
+}
+
+/// A trait for filters that can be merged with another filter of the same
+/// kind, such as filters trained on different shards of a dataset.
+pub trait MergeFilter: Filter {
+    /// Checks if the filter can be merged with `other`, i.e. if both were
+    /// built with the same parameters.
+    fn can_merge(&self, other: &Self) -> bool;
+
+    /// Merges the members of `other` into the filter, returning an error if
+    /// the filters cannot be merged.
+    fn try_merge(&mut self, other: &Self) -> WisardResult<()>;
+
+    /// Merges the members of `other` into the filter.
+    ///
+    /// Panics if the filters cannot be merged.
+    fn merge(&mut self, other: &Self) {
+        self.try_merge(other).unwrap_or_else(|err| panic!("{err}"))
+    }
 }
 
 /// A trait for filter builders.
@@ -1,5 +1,7 @@
 pub mod dataset;
 pub mod encode;
+pub mod error;
+pub mod eval;
 pub mod filter;
 pub mod model;
 pub mod sample;
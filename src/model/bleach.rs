@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+/// A bleaching strategy for untying the scores of counting discriminators.
+///
+/// Bleaching raises the minimum counter value required for a RAM to fire
+/// when more than one discriminator achieves the highest score. The two
+/// strategies pick different thresholds, so they may predict different
+/// labels for the same counters.
+#[derive(
+    Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize,
+)]
+pub enum Bleaching {
+    /// Increments the threshold by one until a single discriminator achieves
+    /// the highest score. If every discriminator scores zero at a given
+    /// threshold, the scores computed at the previous threshold are used.
+    #[default]
+    Linear,
+    /// Finds the highest threshold at which some discriminator still has a
+    /// non-zero score with a binary search, then decrements it until a
+    /// single discriminator achieves the highest score.
+    ///
+    /// Whether the scores are tied does not change monotonically with the
+    /// threshold, so this may pick a higher threshold than
+    /// [`Linear`](#variant.Linear) and predict a different label. If no
+    /// threshold unties the scores, the highest one is used.
+    BinarySearch,
+}
+
+impl Bleaching {
+    /// Returns the bleached scores for the given per-discriminator counters.
+    ///
+    /// Each element of `counters` holds the counters addressed by a sample in
+    /// every RAM of a single discriminator, and the scores are returned in
+    /// the same order.
+    pub fn scores(&self, counters: &[Vec<usize>]) -> Vec<usize> {
+        scores_at(counters, self.threshold(counters))
+    }
+
+    /// Returns the threshold used to compute the bleached scores for the
+    /// given per-discriminator counters. A RAM fires if its counter is
+    /// larger than the threshold.
+    pub fn threshold(&self, counters: &[Vec<usize>]) -> usize {
+        let max_counter = counters
+            .iter()
+            .flat_map(|c| c.iter().copied())
+            .max()
+            .unwrap_or(0);
+
+        if is_untied(&scores_at(counters, 0)) {
+            return 0;
+        }
+
+        let mut best = 0;
+
+        match self {
+            Self::Linear => {
+                for threshold in 1..max_counter {
+                    let scores = scores_at(counters, threshold);
+                    if scores.iter().all(|&s| s == 0) {
+                        break;
+                    }
+                    best = threshold;
+                    if is_untied(&scores) {
+                        break;
+                    }
+                }
+            }
+            Self::BinarySearch => {
+                let mut low = 1;
+                let mut high = max_counter;
+                while low < high {
+                    let mid = low + (high - low) / 2;
+                    let scores = scores_at(counters, mid);
+                    if scores.iter().all(|&s| s == 0) {
+                        high = mid;
+                    } else {
+                        best = mid;
+                        low = mid + 1;
+                    }
+                }
+
+                if let Some(threshold) = (1..=best).rev().find(|&threshold| {
+                    is_untied(&scores_at(counters, threshold))
+                }) {
+                    best = threshold;
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// Returns the number of counters larger than `threshold` of each
+/// discriminator.
+fn scores_at(counters: &[Vec<usize>], threshold: usize) -> Vec<usize> {
+    counters
+        .iter()
+        .map(|c| c.iter().filter(|&&v| v > threshold).count())
+        .collect()
+}
+
+/// Returns `true` if there is a single maximum score or all scores are zero.
+fn is_untied(scores: &[usize]) -> bool {
+    let max = scores.iter().copied().max().unwrap_or(0);
+    max == 0 || scores.iter().filter(|&&s| s == max).count() == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bleaching_untied() {
+        let counters = vec![vec![1, 1, 0], vec![1, 0, 0]];
+        let expected = vec![2, 1];
+        assert_eq!(Bleaching::Linear.scores(&counters), expected);
+        assert_eq!(Bleaching::BinarySearch.scores(&counters), expected);
+    }
+
+    #[test]
+    fn bleaching_tied() {
+        let counters = vec![vec![4, 3, 1], vec![2, 2, 2], vec![5, 1, 1]];
+        assert_eq!(Bleaching::Linear.scores(&counters), vec![2, 3, 1]);
+        assert_eq!(Bleaching::BinarySearch.scores(&counters), vec![0, 0, 1]);
+        assert_eq!(Bleaching::Linear.threshold(&counters), 1);
+        assert_eq!(Bleaching::BinarySearch.threshold(&counters), 4);
+    }
+
+    #[test]
+    fn bleaching_binary_search_untied() {
+        let counters = vec![vec![6, 6, 1], vec![6, 6, 2]];
+        assert_eq!(Bleaching::Linear.scores(&counters), vec![2, 3]);
+        assert_eq!(Bleaching::BinarySearch.scores(&counters), vec![2, 3]);
+        let counters = vec![vec![6, 3, 3], vec![6, 2, 4]];
+        assert_eq!(Bleaching::Linear.threshold(&counters), 2);
+        assert_eq!(Bleaching::BinarySearch.threshold(&counters), 3);
+        assert_eq!(Bleaching::BinarySearch.scores(&counters), vec![1, 2]);
+    }
+
+    #[test]
+    fn bleaching_saturated() {
+        let counters = vec![vec![3, 3], vec![3, 3]];
+        let expected = vec![2, 2];
+        assert_eq!(Bleaching::Linear.scores(&counters), expected);
+        assert_eq!(Bleaching::BinarySearch.scores(&counters), expected);
+    }
+}
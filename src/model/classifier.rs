@@ -0,0 +1,258 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::WisardResult,
+    filter::{BuildFilter, Filter},
+    model::{BinaryWisard, ClusWisard, CountingWisard, WisardBase},
+    sample::{Label, Sample},
+};
+
+/// A trait for models that classify samples into labels.
+///
+/// The panicking methods are provided in terms of their fallible `try_*`
+/// counterparts, so implementors only need to provide the latter.
+pub trait Classifier<L, T = usize, O = LocalBits>
+where
+    L: Label,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    /// Fits (trains) the model with a given input sample, returning an error
+    /// if the sample is invalid or its label is unknown.
+    fn try_fit(&mut self, sample: &Sample<L, T, O>) -> WisardResult<()>;
+
+    /// Returns the model scores for a given input sample, or an error if the
+    /// sample is invalid.
+    fn try_scores(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>>;
+
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample is invalid or the model has no labels.
+    fn try_predict(&self, sample: &Sample<L, T, O>) -> WisardResult<L>;
+
+    /// Fits (trains) the model with a given input sample.
+    fn fit(&mut self, sample: &Sample<L, T, O>) {
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model scores for a given input sample.
+    fn scores(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)> {
+        self.try_scores(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample.
+    fn predict(&self, sample: &Sample<L, T, O>) -> L {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with every sample of an iterator, in order,
+    /// stopping at the first invalid sample.
+    fn try_fit_iter<'a, I>(&mut self, samples: I) -> WisardResult<()>
+    where
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+        L: 'a,
+        T: 'a,
+        O: 'a,
+    {
+        samples
+            .into_iter()
+            .try_for_each(|sample| self.try_fit(sample))
+    }
+
+    /// Fits (trains) the model with every sample of an iterator, in order.
+    fn fit_iter<'a, I>(&mut self, samples: I)
+    where
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+        L: 'a,
+        T: 'a,
+        O: 'a,
+    {
+        self.try_fit_iter(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model predictions for every sample of an iterator, or an
+    /// error if any sample is invalid or the model has no labels.
+    fn try_predict_iter<'a, I>(&self, samples: I) -> WisardResult<Vec<L>>
+    where
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+        L: 'a,
+        T: 'a,
+        O: 'a,
+    {
+        samples
+            .into_iter()
+            .map(|sample| self.try_predict(sample))
+            .collect()
+    }
+
+    /// Returns the model predictions for every sample of an iterator.
+    fn predict_iter<'a, I>(&self, samples: I) -> Vec<L>
+    where
+        I: IntoIterator<Item = &'a Sample<L, T, O>>,
+        L: 'a,
+        T: 'a,
+        O: 'a,
+    {
+        self.try_predict_iter(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<L, T, O> Classifier<L, T, O> for BinaryWisard<L>
+where
+    L: Label,
+    T: BitStore + Clone + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder + Clone,
+{
+    fn try_fit(&mut self, sample: &Sample<L, T, O>) -> WisardResult<()> {
+        BinaryWisard::try_fit(self, sample)
+    }
+
+    fn try_scores(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>> {
+        BinaryWisard::try_scores(self, sample)
+    }
+
+    fn try_predict(&self, sample: &Sample<L, T, O>) -> WisardResult<L> {
+        BinaryWisard::try_predict(self, sample)
+    }
+}
+
+impl<L, T, O> Classifier<L, T, O> for CountingWisard<L>
+where
+    L: Label,
+    T: BitStore + Clone + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder + Clone,
+{
+    fn try_fit(&mut self, sample: &Sample<L, T, O>) -> WisardResult<()> {
+        CountingWisard::try_fit(self, sample)
+    }
+
+    fn try_scores(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>> {
+        CountingWisard::try_scores(self, sample)
+    }
+
+    fn try_predict(&self, sample: &Sample<L, T, O>) -> WisardResult<L> {
+        CountingWisard::try_predict(self, sample)
+    }
+}
+
+impl<L, F, T, O> Classifier<L, T, O> for WisardBase<L, F>
+where
+    L: Label,
+    F: Filter,
+    T: BitStore + Clone + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder + Clone,
+{
+    fn try_fit(&mut self, sample: &Sample<L, T, O>) -> WisardResult<()> {
+        WisardBase::try_fit(self, sample)
+    }
+
+    fn try_scores(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>> {
+        WisardBase::try_scores(self, sample)
+    }
+
+    fn try_predict(&self, sample: &Sample<L, T, O>) -> WisardResult<L> {
+        WisardBase::try_predict(self, sample)
+    }
+}
+
+impl<L, B, T, O> Classifier<L, T, O> for ClusWisard<L, B>
+where
+    L: Label,
+    B: BuildFilter,
+    T: BitStore + DeserializeOwned,
+    T::Mem: Serialize,
+    O: BitOrder,
+{
+    fn try_fit(&mut self, sample: &Sample<L, T, O>) -> WisardResult<()> {
+        ClusWisard::try_fit(self, sample)
+    }
+
+    fn try_scores(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>> {
+        ClusWisard::try_scores(self, sample)
+    }
+
+    fn try_predict(&self, sample: &Sample<L, T, O>) -> WisardResult<L> {
+        ClusWisard::try_predict(self, sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::filter::PackedLUTFilterBuilder;
+
+    fn samples() -> Vec<Sample<usize, u8, Msb0>> {
+        vec![
+            (bitvec![u8, Msb0; 1, 1, 1, 1, 0, 0, 0, 0], 0),
+            (bitvec![u8, Msb0; 1, 1, 1, 0, 0, 0, 0, 0], 0),
+            (bitvec![u8, Msb0; 0, 0, 0, 0, 1, 1, 1, 1], 1),
+            (bitvec![u8, Msb0; 0, 0, 0, 1, 1, 1, 1, 1], 1),
+        ]
+        .into_iter()
+        .map(|(bits, label)| Sample::from_raw_parts(bits, 1, label))
+        .collect()
+    }
+
+    fn fit_and_predict<C>(model: &mut C) -> Vec<usize>
+    where
+        C: Classifier<usize, u8, Msb0>,
+    {
+        let samples = samples();
+        model.fit_iter(samples.iter());
+        model.predict_iter(samples.iter())
+    }
+
+    #[test]
+    fn classifier_models() {
+        let labels = HashSet::from_iter([0usize, 1]);
+        let expected = vec![0, 0, 1, 1];
+
+        let mut binary = BinaryWisard::with_seed(8, 2, labels.clone(), [1; 32]);
+        assert_eq!(fit_and_predict(&mut binary), expected);
+
+        let mut counting =
+            CountingWisard::with_seed(8, 2, 2, labels.clone(), [1; 32]);
+        assert_eq!(fit_and_predict(&mut counting), expected);
+
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        let mut base =
+            WisardBase::from_filter_builder(8, 2, labels.clone(), &builder);
+        assert_eq!(fit_and_predict(&mut base), expected);
+
+        let mut clus = ClusWisard::from_filter_builder(8, 2, labels, &builder);
+        assert_eq!(fit_and_predict(&mut clus), expected);
+    }
+
+    #[test]
+    fn classifier_iter_errors() {
+        let labels = HashSet::from_iter([0usize]);
+        let mut model = BinaryWisard::with_seed(8, 2, labels, [1; 32]);
+        let result = Classifier::try_fit_iter(&mut model, samples().iter());
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,544 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use bitvec::prelude::*;
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    error::{WisardError, WisardResult},
+    filter::{BuildFilter, Filter, PackedLUTFilterBuilder},
+    model::{best_label, Discriminator, TupleMapping},
+    sample::{Label, Sample},
+};
+
+/// A ClusWiSARD model, where each label owns a growing set of
+/// discriminators instead of a single one.
+///
+/// When fitting a sample, the discriminator of its label with the highest
+/// score is trained if that score reaches the learning threshold, which is
+/// given as a fraction of the number of RAMs. Otherwise, a new discriminator
+/// is created for the sample, unless the label already has the maximum
+/// number of discriminators, in which case the best one is trained anyway.
+/// This lets each discriminator specialize in a different mode of a
+/// multimodal class.
+///
+/// The score of a label is the highest score among its discriminators.
+/// Unlabeled samples can also be used for training, with
+/// [`fit_unlabeled`](#method.fit_unlabeled).
+///
+/// The tuple mapping is shared by all the discriminators, and it is stored
+/// only once when the model is serialized.
+#[derive(Clone, Debug)]
+pub struct ClusWisard<L, B = PackedLUTFilterBuilder>
+where
+    L: Label,
+    B: BuildFilter,
+{
+    mapping: Arc<TupleMapping>,
+    builder: B,
+    min_score: f64,
+    max_discriminators: usize,
+    clusters: HashMap<L, Vec<Discriminator<B::Filter>>>,
+}
+
+/// The serialized form of a [`ClusWisard`](./struct.ClusWisard.html) model.
+#[derive(Serialize, Deserialize)]
+struct ClusWisardRepr<M, B, D> {
+    mapping: M,
+    builder: B,
+    min_score: f64,
+    max_discriminators: usize,
+    clusters: D,
+}
+
+impl<L, B> Serialize for ClusWisard<L, B>
+where
+    L: Label,
+    B: BuildFilter + Serialize,
+    B::Filter: Serialize,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let clusters = self
+            .clusters
+            .iter()
+            .map(|(label, discs)| {
+                (label, discs.iter().map(|d| d.filters()).collect::<Vec<_>>())
+            })
+            .collect::<HashMap<_, _>>();
+        let repr = ClusWisardRepr {
+            mapping: self.mapping.as_ref(),
+            builder: &self.builder,
+            min_score: self.min_score,
+            max_discriminators: self.max_discriminators,
+            clusters,
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, L, B> Deserialize<'de> for ClusWisard<L, B>
+where
+    L: Label,
+    B: BuildFilter + DeserializeOwned,
+    B::Filter: DeserializeOwned,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        type Clusters<L, F> = HashMap<L, Vec<Vec<F>>>;
+        let repr = ClusWisardRepr::<
+            TupleMapping,
+            B,
+            Clusters<L, <B as BuildFilter>::Filter>,
+        >::deserialize(deserializer)?;
+        let mapping = Arc::new(repr.mapping);
+        let clusters = repr
+            .clusters
+            .into_iter()
+            .map(|(label, discs)| {
+                let discs = discs
+                    .into_iter()
+                    .map(|filters| shared_discriminator(&mapping, filters))
+                    .collect::<Result<_, _>>()?;
+                Ok((label, discs))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        Ok(Self {
+            mapping,
+            builder: repr.builder,
+            min_score: repr.min_score,
+            max_discriminators: repr.max_discriminators,
+            clusters,
+        })
+    }
+}
+
+/// Builds a deserialized discriminator that uses a shared `mapping`.
+fn shared_discriminator<F, E>(
+    mapping: &Arc<TupleMapping>,
+    filters: Vec<F>,
+) -> Result<Discriminator<F>, E>
+where
+    F: Filter,
+    E: de::Error,
+{
+    if filters.len() != mapping.len() {
+        return Err(E::custom("discriminator size does not match the mapping"));
+    }
+
+    Ok(Discriminator::from_filters(mapping.clone(), filters))
+}
+
+impl<L, B> ClusWisard<L, B>
+where
+    L: Label,
+    B: BuildFilter,
+{
+    /// Creates a new [`ClusWisard`](./struct.ClusWisard.html) instance with
+    /// a learning threshold of `0.5` and at most `8` discriminators per
+    /// label.
+    ///
+    /// The `input_size` value determines the total number of input bits.
+    /// The `addr_size` value corresponds to the address size of the RAMs.
+    /// The `labels` set must contain all the expected sample labels.
+    /// The `builder` value is cloned into the model and used to build the
+    /// RAMs of every new discriminator, using the same `addr_size` as
+    /// provided before.
+    pub fn from_filter_builder(
+        input_size: usize,
+        addr_size: usize,
+        labels: HashSet<L>,
+        builder: &B,
+    ) -> Self
+    where
+        B: Clone,
+    {
+        let mapping = TupleMapping::contiguous(input_size, addr_size);
+        Self::from_mapping(mapping, labels, builder)
+    }
+
+    /// Creates a new [`ClusWisard`](./struct.ClusWisard.html) instance using
+    /// a given [`TupleMapping`](./struct.TupleMapping.html), which is shared
+    /// by all the discriminators.
+    ///
+    /// The `mapping` value determines which input bits feed each RAM.
+    /// The `labels` set must contain all the expected sample labels.
+    /// The `builder` value is cloned into the model and used to build the
+    /// RAMs of every new discriminator, using the address size of the
+    /// `mapping`.
+    pub fn from_mapping(
+        mapping: TupleMapping,
+        labels: HashSet<L>,
+        builder: &B,
+    ) -> Self
+    where
+        B: Clone,
+    {
+        let clusters = labels.into_iter().map(|l| (l, Vec::new())).collect();
+        Self {
+            mapping: Arc::new(mapping),
+            builder: builder.clone(),
+            min_score: 0.5,
+            max_discriminators: 8,
+            clusters,
+        }
+    }
+
+    /// Sets the learning threshold, as a fraction of the number of RAMs
+    /// between zero and one.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&min_score),
+            "learning threshold must be between zero and one"
+        );
+        self.min_score = min_score;
+        self
+    }
+
+    /// Sets the maximum number of discriminators of each label, which must
+    /// not be zero.
+    pub fn with_max_discriminators(
+        mut self,
+        max_discriminators: usize,
+    ) -> Self {
+        assert!(
+            max_discriminators > 0,
+            "ClusWisard requires at least one discriminator per label"
+        );
+        self.max_discriminators = max_discriminators;
+        self
+    }
+
+    /// Returns the model input size.
+    pub fn input_size(&self) -> usize {
+        self.mapping.input_size()
+    }
+
+    /// Returns the model address size.
+    pub fn addr_size(&self) -> usize {
+        self.mapping.addr_size()
+    }
+
+    /// Returns the tuple mapping shared by all the discriminators.
+    pub fn mapping(&self) -> &TupleMapping {
+        &self.mapping
+    }
+
+    /// Returns the learning threshold, as a fraction of the number of RAMs.
+    pub fn min_score(&self) -> f64 {
+        self.min_score
+    }
+
+    /// Returns the maximum number of discriminators of each label.
+    pub fn max_discriminators(&self) -> usize {
+        self.max_discriminators
+    }
+
+    /// Returns an iterator over the model labels.
+    pub fn labels(&self) -> impl Iterator<Item = &L> {
+        self.clusters.keys()
+    }
+
+    /// Returns the discriminators of a given label, or `None` if the label
+    /// is unknown.
+    pub fn discriminators(
+        &self,
+        label: &L,
+    ) -> Option<&[Discriminator<B::Filter>]> {
+        self.clusters.get(label).map(Vec::as_slice)
+    }
+
+    /// Returns the RAM addresses of a given input sample, which are shared
+    /// by all the discriminators, or an error if the sample size does not
+    /// match the input size.
+    fn addresses<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<usize>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        if sample.len() != self.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: self.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        Ok(self.mapping.addresses(sample.raw_bits()).collect())
+    }
+
+    /// Returns `true` if a score reaches the learning threshold.
+    fn is_learnable(&self, score: usize) -> bool {
+        score as f64 >= self.min_score * self.mapping.len() as f64
+    }
+
+    /// Returns the index and score of the best discriminator of a set.
+    fn best_discriminator(
+        discs: &[Discriminator<B::Filter>],
+        addrs: &[usize],
+    ) -> WisardResult<Option<(usize, usize)>> {
+        let mut best = None;
+
+        for (index, disc) in discs.iter().enumerate() {
+            let score = disc.score_addresses(addrs.iter().copied())?;
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((index, score));
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Fits (trains) the model with a given input sample.
+    pub fn fit<T, O>(&mut self, sample: &Sample<L, T, O>)
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a given input sample, returning an error
+    /// if the sample is invalid or its label is unknown.
+    pub fn try_fit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let addrs = self.addresses(sample)?;
+        let discs = self.clusters.get(sample.label()).ok_or_else(|| {
+            WisardError::UnknownLabel(format!("{:?}", sample.label()))
+        })?;
+
+        let index = match Self::best_discriminator(discs, &addrs)? {
+            Some((index, score)) if self.is_learnable(score) => index,
+            Some((index, _)) if discs.len() >= self.max_discriminators => index,
+            _ => {
+                let disc = Discriminator::from_shared_mapping(
+                    self.mapping.clone(),
+                    &self.builder,
+                );
+                let discs = self.clusters.get_mut(sample.label()).unwrap();
+                discs.push(disc);
+                discs.len() - 1
+            }
+        };
+
+        let discs = self.clusters.get_mut(sample.label()).unwrap();
+        discs[index].fit_addresses(addrs)
+    }
+
+    /// Fits (trains) the model with a given input sample, ignoring its
+    /// label, and returns the label it was assigned to, if any.
+    ///
+    /// The sample trains the best discriminator among all labels if its
+    /// score reaches the learning threshold, and is discarded otherwise, so
+    /// only confidently recognized samples are learned.
+    pub fn fit_unlabeled<T, O>(&mut self, sample: &Sample<L, T, O>) -> Option<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_fit_unlabeled(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a given input sample, ignoring its
+    /// label, and returns the label it was assigned to, if any, or an error
+    /// if the sample is invalid.
+    pub fn try_fit_unlabeled<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Option<L>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let addrs = self.addresses(sample)?;
+        let mut best = None;
+
+        for (label, discs) in self.clusters.iter() {
+            if let Some((index, score)) =
+                Self::best_discriminator(discs, &addrs)?
+            {
+                if best.is_none_or(|(_, _, best_score)| score > best_score) {
+                    best = Some((*label, index, score));
+                }
+            }
+        }
+
+        match best {
+            Some((label, index, score)) if self.is_learnable(score) => {
+                let discs = self.clusters.get_mut(&label).unwrap();
+                discs[index].fit_addresses(addrs)?;
+                Ok(Some(label))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the model scores for a given input sample.
+    pub fn scores<T, O>(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_scores(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model scores for a given input sample, or an error if the
+    /// sample is invalid. Labels without discriminators have a score of zero.
+    pub fn try_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let addrs = self.addresses(sample)?;
+        self.clusters
+            .iter()
+            .map(|(label, discs)| {
+                let best = Self::best_discriminator(discs, &addrs)?;
+                Ok((best.map_or(0, |(_, score)| score), *label))
+            })
+            .collect()
+    }
+
+    /// Returns the model prediction for a given input sample.
+    pub fn predict<T, O>(&self, sample: &Sample<L, T, O>) -> L
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample is invalid or the model has no labels.
+    pub fn try_predict<T, O>(&self, sample: &Sample<L, T, O>) -> WisardResult<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        best_label(self.try_scores(sample)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use super::*;
+
+    fn sample(bits: BitVec, label: usize) -> Sample<usize> {
+        Sample::from_raw_parts(bits, 1, label)
+    }
+
+    fn model() -> ClusWisard<usize> {
+        let labels = HashSet::from_iter([0, 1]);
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        ClusWisard::from_filter_builder(12, 2, labels, &builder)
+            .with_min_score(0.5)
+            .with_max_discriminators(4)
+    }
+
+    #[test]
+    fn clus_wisard_multimodal() {
+        let mut model = model();
+        let left = bitvec![1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        let right = bitvec![0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1];
+        let middle = bitvec![0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0];
+
+        model.fit(&sample(left.clone(), 0));
+        model.fit(&sample(right.clone(), 0));
+        model.fit(&sample(left.clone(), 0));
+        model.fit(&sample(middle.clone(), 1));
+
+        assert_eq!(model.discriminators(&0).unwrap().len(), 2);
+        assert_eq!(model.discriminators(&1).unwrap().len(), 1);
+        assert_eq!(model.predict(&sample(left, 1)), 0);
+        assert_eq!(model.predict(&sample(right, 1)), 0);
+        assert_eq!(model.predict(&sample(middle, 0)), 1);
+    }
+
+    #[test]
+    fn clus_wisard_max_discriminators() {
+        let mut model = model().with_max_discriminators(1);
+        model.fit(&sample(bitvec![1; 12], 0));
+        model.fit(&sample(bitvec![0; 12], 0));
+        assert_eq!(model.discriminators(&0).unwrap().len(), 1);
+        assert_eq!(model.scores(&sample(bitvec![0; 12], 0)).len(), 2);
+    }
+
+    #[test]
+    fn clus_wisard_serde_round_trip() {
+        let mut model = model();
+        let left = bitvec![1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        let right = bitvec![0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1];
+        model.fit(&sample(left.clone(), 0));
+        model.fit(&sample(right.clone(), 0));
+
+        let bytes = bincode::serialize(&model).unwrap();
+        let loaded: ClusWisard<usize> = bincode::deserialize(&bytes).unwrap();
+        let discs = loaded.discriminators(&0).unwrap();
+        assert_eq!(discs.len(), 2);
+        assert!(discs
+            .iter()
+            .all(|disc| std::ptr::eq(disc.mapping(), loaded.mapping())));
+        assert_eq!(loaded.predict(&sample(right, 1)), 0);
+    }
+
+    #[test]
+    fn clus_wisard_unlabeled() {
+        let mut model = model();
+        model.fit(&sample(bitvec![1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0], 0));
+
+        let close = sample(bitvec![1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0], 1);
+        assert_eq!(model.fit_unlabeled(&close), Some(0));
+        assert_eq!(model.discriminators(&0).unwrap().len(), 1);
+
+        let far = sample(bitvec![1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0], 1);
+        assert_eq!(model.fit_unlabeled(&far), None);
+        assert!(model.discriminators(&1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn clus_wisard_errors() {
+        let mut model = model();
+        assert!(matches!(
+            model.try_fit(&sample(bitvec![0; 12], 2)),
+            Err(WisardError::UnknownLabel(_)),
+        ));
+        assert!(matches!(
+            model.try_predict(&sample(bitvec![0; 4], 0)),
+            Err(WisardError::InputSizeMismatch { .. }),
+        ));
+    }
+}
@@ -1,19 +1,31 @@
+use std::sync::Arc;
+
 use bitvec::prelude::*;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    filter::{BuildFilter, Filter},
+    error::{WisardError, WisardResult},
+    filter::{
+        BuildFilter, CountingFilter, DecrementFilter, Filter, MergeFilter,
+    },
+    model::TupleMapping,
     sample::{Label, Sample},
 };
 
 /// A WiSARD discriminator structure.
-#[derive(Clone, Debug)]
+///
+/// The tuple mapping is reference counted, so the discriminators of a model
+/// share a single copy of it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "F: Serialize",
+    deserialize = "F: DeserializeOwned"
+))]
 pub struct Discriminator<F>
 where
     F: Filter,
 {
-    input_size: usize,
-    addr_size: usize,
+    mapping: Arc<TupleMapping>,
     filters: Vec<F>,
 }
 
@@ -36,28 +48,66 @@ where
     where
         B: BuildFilter<Filter = F>,
     {
-        let num_filters = (input_size + addr_size - 1) / addr_size;
-        let mut filters = Vec::with_capacity(num_filters);
+        let mapping = TupleMapping::contiguous(input_size, addr_size);
+        Self::from_mapping(mapping, builder)
+    }
 
-        for _ in 0..num_filters {
-            filters.push(builder.build_filter());
-        }
+    /// Creates a new [`Discriminator`](./struct.Discriminator.html) instance
+    /// using a given [`TupleMapping`](./struct.TupleMapping.html).
+    ///
+    /// The `mapping` value determines which input bits feed each RAM.
+    /// The `builder` value must be an instance of a type which implements
+    /// the [`FilterBuilder`](./trait.FilterBuilder.html) trait, using the
+    /// address size of the `mapping` and serving as a backend for the RAMs.
+    pub fn from_mapping<B>(mapping: TupleMapping, builder: &B) -> Self
+    where
+        B: BuildFilter<Filter = F>,
+    {
+        Self::from_shared_mapping(Arc::new(mapping), builder)
+    }
 
-        Self {
-            input_size,
-            addr_size,
-            filters,
-        }
+    /// Creates a new [`Discriminator`](./struct.Discriminator.html) instance
+    /// using a [`TupleMapping`](./struct.TupleMapping.html) shared with other
+    /// discriminators.
+    pub(crate) fn from_shared_mapping<B>(
+        mapping: Arc<TupleMapping>,
+        builder: &B,
+    ) -> Self
+    where
+        B: BuildFilter<Filter = F>,
+    {
+        let filters =
+            (0..mapping.len()).map(|_| builder.build_filter()).collect();
+        Self { mapping, filters }
+    }
+
+    /// Creates a [`Discriminator`](./struct.Discriminator.html) instance
+    /// from an already built set of filters.
+    pub(crate) fn from_filters(
+        mapping: Arc<TupleMapping>,
+        filters: Vec<F>,
+    ) -> Self {
+        Self { mapping, filters }
     }
 
     /// Returns the discriminator input size.
     pub fn input_size(&self) -> usize {
-        self.input_size
+        self.mapping.input_size()
     }
 
     /// Returns the discriminator address size.
     pub fn addr_size(&self) -> usize {
-        self.addr_size
+        self.mapping.addr_size()
+    }
+
+    /// Returns the tuple mapping used to address the RAMs.
+    pub fn mapping(&self) -> &TupleMapping {
+        &self.mapping
+    }
+
+    /// Returns the discriminator filters (RAMs).
+    pub fn filters(&self) -> &[F] {
+        &self.filters
     }
 
     /// Fits (trains) the discriminator with a given input sample.
@@ -68,15 +118,40 @@ where
         T::Mem: Serialize,
         O: BitOrder,
     {
-        sample
-            .raw_bits()
-            .chunks(self.addr_size)
-            .enumerate()
-            .for_each(|(i, v)| {
-                let mut addr = 0usize;
-                addr.view_bits_mut::<O>()[..v.len()].clone_from_bitslice(v);
-                self.filters[i].include(&addr);
-            })
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the discriminator with a given input sample, returning
+    /// an error if the sample size does not match the input size.
+    pub fn try_fit<L, T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.check_input_size(sample)?;
+        let addrs = self
+            .mapping
+            .addresses(sample.raw_bits())
+            .collect::<Vec<_>>();
+        self.fit_addresses(addrs)
+    }
+
+    /// Fits (trains) the discriminator with the RAM addresses computed by
+    /// the discriminator mapping for a given input sample.
+    pub(crate) fn fit_addresses<I>(&mut self, addrs: I) -> WisardResult<()>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        for (filter, addr) in self.filters.iter_mut().zip(addrs) {
+            filter.try_include(&addr)?;
+        }
+
+        Ok(())
     }
 
     /// Returns the discriminator score for a given input sample.
@@ -87,17 +162,229 @@ where
         T::Mem: Serialize,
         O: BitOrder,
     {
-        sample
-            .raw_bits()
-            .chunks(self.addr_size)
-            .enumerate()
-            .map(|(i, v)| {
-                let mut addr = 0usize;
-                addr.view_bits_mut::<O>()[..v.len()].clone_from_bitslice(v);
-                self.filters[i].contains(&addr) as usize
-            })
+        self.try_score(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the discriminator score for a given input sample, or an error
+    /// if the sample size does not match the input size.
+    pub fn try_score<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<usize>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.check_input_size(sample)?;
+        self.score_addresses(self.mapping.addresses(sample.raw_bits()))
+    }
+
+    /// Returns the discriminator score for the RAM addresses computed by the
+    /// discriminator mapping for a given input sample.
+    pub(crate) fn score_addresses<I>(&self, addrs: I) -> WisardResult<usize>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.filters
+            .iter()
+            .zip(addrs)
+            .map(|(filter, addr)| filter.try_contains(&addr).map(usize::from))
             .sum()
     }
+
+    /// Checks if the sample size matches the discriminator input size.
+    fn check_input_size<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        if sample.len() != self.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: self.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<F> Discriminator<F>
+where
+    F: CountingFilter,
+{
+    /// Returns the RAM counters addressed by a given input sample.
+    pub fn counters<L, T, O>(&self, sample: &Sample<L, T, O>) -> Vec<usize>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_counters(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the RAM counters addressed by a given input sample, or an
+    /// error if the sample size does not match the input size.
+    pub fn try_counters<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<usize>>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.check_input_size(sample)?;
+        self.counters_addresses(self.mapping.addresses(sample.raw_bits()))
+    }
+
+    /// Returns the RAM counters for the RAM addresses computed by the
+    /// discriminator mapping for a given input sample.
+    pub(crate) fn counters_addresses<I>(
+        &self,
+        addrs: I,
+    ) -> WisardResult<Vec<usize>>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.filters
+            .iter()
+            .zip(addrs)
+            .map(|(filter, addr)| {
+                filter.try_counter(&addr).map(|c| c.unwrap_or(0))
+            })
+            .collect()
+    }
+
+    /// Returns the discriminator score for a given input sample, only
+    /// counting the RAMs whose counters are above `threshold`.
+    pub fn score_with_threshold<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        threshold: usize,
+    ) -> usize
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.counters(sample)
+            .into_iter()
+            .filter(|&count| count > threshold)
+            .count()
+    }
+}
+
+impl<F> Discriminator<F>
+where
+    F: DecrementFilter,
+{
+    /// Unfits (untrains) the discriminator with a given input sample,
+    /// reverting a previous call to [`fit`](#method.fit) with it.
+    pub fn unfit<L, T, O>(&mut self, sample: &Sample<L, T, O>)
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_unfit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Unfits (untrains) the discriminator with a given input sample,
+    /// returning an error if the sample size does not match the input size.
+    pub fn try_unfit<L, T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.check_input_size(sample)?;
+        let addrs = self
+            .mapping
+            .addresses(sample.raw_bits())
+            .collect::<Vec<_>>();
+        self.unfit_addresses(addrs)
+    }
+
+    /// Unfits (untrains) the discriminator with the RAM addresses computed
+    /// by the discriminator mapping for a given input sample.
+    pub(crate) fn unfit_addresses<I>(&mut self, addrs: I) -> WisardResult<()>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        for (filter, addr) in self.filters.iter_mut().zip(addrs) {
+            filter.try_exclude(&addr)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F> Discriminator<F>
+where
+    F: MergeFilter,
+{
+    /// Merges the RAMs of `other` into the discriminator, as if it was also
+    /// fitted with every sample used to fit `other`.
+    pub fn merge(&mut self, other: &Self) {
+        self.try_merge(other).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Merges the RAMs of `other` into the discriminator, returning an error
+    /// if their mappings or filter parameters do not match. The
+    /// discriminator is left unchanged in case of errors.
+    pub fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        self.check_merge(other)?;
+
+        for (filter, other) in self.filters.iter_mut().zip(other.filters.iter())
+        {
+            filter.try_merge(other)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the discriminator can be merged with `other`.
+    pub(crate) fn check_merge(&self, other: &Self) -> WisardResult<()> {
+        if !Arc::ptr_eq(&self.mapping, &other.mapping)
+            && self.mapping != other.mapping
+        {
+            return Err(WisardError::IncompatibleMerge(
+                "discriminators have different mappings",
+            ));
+        }
+
+        let compatible = self.filters.len() == other.filters.len()
+            && self
+                .filters
+                .iter()
+                .zip(other.filters.iter())
+                .all(|(filter, other)| filter.can_merge(other));
+
+        if !compatible {
+            return Err(WisardError::IncompatibleMerge(
+                "discriminators have different filter parameters",
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +414,62 @@ mod tests {
         samples.iter().map(|sample| disc.score(sample)).collect()
     }
 
+    #[test]
+    fn discriminator_counters() {
+        let builder = PackedLUTFilterBuilder::new(2, 4, 0);
+        let mut disc = Discriminator::from_filter_builder(4, 2, &builder);
+        let sample_1 = Sample::from_raw_parts(bitvec![0, 0, 1, 1], 2, 0usize);
+        let sample_2 = Sample::from_raw_parts(bitvec![0, 0, 0, 1], 2, 0usize);
+        disc.fit(&sample_1);
+        disc.fit(&sample_1);
+        disc.fit(&sample_2);
+        assert_eq!(disc.counters(&sample_1), vec![3, 2]);
+        assert_eq!(disc.counters(&sample_2), vec![3, 1]);
+        assert_eq!(disc.score_with_threshold(&sample_1, 1), 2);
+        assert_eq!(disc.score_with_threshold(&sample_2, 1), 1);
+        assert_eq!(disc.score_with_threshold(&sample_2, 2), 1);
+    }
+
+    #[test]
+    fn discriminator_merge() {
+        let builder = PackedLUTFilterBuilder::new(2, 4, 0);
+        let mut disc = Discriminator::from_filter_builder(4, 2, &builder);
+        let mut other = Discriminator::from_filter_builder(4, 2, &builder);
+        let sample_1 = Sample::from_raw_parts(bitvec![0, 0, 1, 1], 2, 0usize);
+        let sample_2 = Sample::from_raw_parts(bitvec![0, 0, 0, 1], 2, 0usize);
+        disc.fit(&sample_1);
+        other.fit(&sample_1);
+        other.fit(&sample_2);
+        disc.merge(&other);
+        assert_eq!(disc.counters(&sample_1), vec![3, 2]);
+        assert_eq!(disc.counters(&sample_2), vec![3, 1]);
+
+        let mapping = TupleMapping::overlapping(4, 2, 1);
+        let incompatible = Discriminator::from_mapping(mapping, &builder);
+        assert!(matches!(
+            disc.try_merge(&incompatible),
+            Err(WisardError::IncompatibleMerge(_)),
+        ));
+        assert_eq!(disc.counters(&sample_1), vec![3, 2]);
+    }
+
+    #[test]
+    fn discriminator_unfit() {
+        let builder = PackedLUTFilterBuilder::new(2, 4, 0);
+        let mut disc = Discriminator::from_filter_builder(4, 2, &builder);
+        let sample_1 = Sample::from_raw_parts(bitvec![0, 0, 1, 1], 2, 0usize);
+        let sample_2 = Sample::from_raw_parts(bitvec![0, 0, 0, 1], 2, 0usize);
+        disc.fit(&sample_1);
+        disc.fit(&sample_2);
+        disc.unfit(&sample_1);
+        assert_eq!(disc.counters(&sample_1), vec![1, 0]);
+        assert_eq!(disc.counters(&sample_2), vec![1, 1]);
+        disc.unfit(&sample_2);
+        disc.unfit(&sample_2);
+        assert_eq!(disc.counters(&sample_2), vec![0, 0]);
+        assert_eq!(disc.score(&sample_1), 0);
+    }
+
     #[test]
     fn discriminator_1ram_4size() {
         let input_size = 4;
@@ -178,4 +521,20 @@ mod tests {
         let found = simple_disc_test(input_size, addr_size, samples);
         assert_eq!(expected, found);
     }
+
+    #[test]
+    fn discriminator_input_size_mismatch() {
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        let mut disc = Discriminator::from_filter_builder(4, 2, &builder);
+        let sample = Sample::from_raw_parts(bitvec![0, 1, 1, 0, 1], 1, 0usize);
+        assert!(matches!(
+            disc.try_fit(&sample),
+            Err(WisardError::InputSizeMismatch {
+                expected: 4,
+                found: 5,
+            }),
+        ));
+        assert!(disc.try_score(&sample).is_err());
+        assert!(disc.try_counters(&sample).is_err());
+    }
 }
@@ -0,0 +1,410 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::{WisardError, WisardResult},
+    filter::CountingFilter,
+    model::{sort_by_score, Bleaching, Discriminator, WisardBase},
+    sample::{Label, Sample},
+};
+
+/// The activation of a single RAM of a discriminator for a given sample.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct RamActivation {
+    index: usize,
+    inputs: Vec<usize>,
+    address: usize,
+    counter: usize,
+    fired: bool,
+}
+
+impl RamActivation {
+    /// Returns the index of the RAM in the discriminator.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the input bit indices that fed the RAM, where the `j`-th
+    /// index is the `j`-th bit of the address.
+    pub fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    /// Returns the address accessed by the sample.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the counter stored at the accessed address.
+    pub fn counter(&self) -> usize {
+        self.counter
+    }
+
+    /// Returns `true` if the RAM fired, i.e. if it contributed to the score.
+    pub fn fired(&self) -> bool {
+        self.fired
+    }
+}
+
+/// The score breakdown of the discriminator of a label.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LabelExplanation<L: Label> {
+    label: L,
+    rams: Vec<RamActivation>,
+}
+
+impl<L: Label> LabelExplanation<L> {
+    /// Returns the explained label.
+    pub fn label(&self) -> &L {
+        &self.label
+    }
+
+    /// Returns the discriminator score, i.e. the number of RAMs that fired.
+    pub fn score(&self) -> usize {
+        self.rams.iter().filter(|ram| ram.fired).count()
+    }
+
+    /// Returns the activation of every RAM of the discriminator.
+    pub fn rams(&self) -> &[RamActivation] {
+        &self.rams
+    }
+
+    /// Returns an iterator over the RAMs that fired.
+    pub fn fired_rams(&self) -> impl Iterator<Item = &RamActivation> {
+        self.rams.iter().filter(|ram| ram.fired)
+    }
+}
+
+/// The explanation of a model prediction, holding the score breakdown of
+/// every label, from the highest score to the lowest.
+///
+/// The first label is the prediction of the model, and the second one, if
+/// any, is the runner-up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Explanation<L: Label> {
+    labels: Vec<LabelExplanation<L>>,
+}
+
+impl<L: Label> Explanation<L> {
+    /// Creates a new [`Explanation`](./struct.Explanation.html) instance,
+    /// sorting the labels like the model predictions.
+    fn new(mut labels: Vec<LabelExplanation<L>>) -> Self {
+        sort_by_score(&mut labels, |explained| {
+            (explained.score(), explained.label)
+        });
+        Self { labels }
+    }
+
+    /// Returns the score breakdown of every label, from the highest score to
+    /// the lowest.
+    pub fn labels(&self) -> &[LabelExplanation<L>] {
+        &self.labels
+    }
+
+    /// Returns the score breakdown of a given label, or `None` if the label
+    /// is unknown.
+    pub fn get(&self, label: &L) -> Option<&LabelExplanation<L>> {
+        self.labels
+            .iter()
+            .find(|explained| explained.label == *label)
+    }
+
+    /// Returns the score breakdown of the predicted label.
+    pub fn winner(&self) -> Option<&LabelExplanation<L>> {
+        self.labels.first()
+    }
+
+    /// Returns the score breakdown of the label with the second highest
+    /// score.
+    pub fn runner_up(&self) -> Option<&LabelExplanation<L>> {
+        self.labels.get(1)
+    }
+
+    /// Returns up to `n` RAMs of the winner that differentiate it from the
+    /// runner-up, i.e. the RAMs that fired for the winner but not for the
+    /// runner-up, ordered by the difference between their counters.
+    ///
+    /// If there is no runner-up, every RAM that fired for the winner is a
+    /// differentiating RAM.
+    pub fn differentiating_rams(&self, n: usize) -> Vec<&RamActivation> {
+        let Some(winner) = self.winner() else {
+            return Vec::new();
+        };
+
+        let runner_up = |index: usize| {
+            self.runner_up().map(|explained| &explained.rams[index])
+        };
+
+        let mut rams = winner
+            .fired_rams()
+            .filter(|ram| !runner_up(ram.index).is_some_and(|r| r.fired))
+            .collect::<Vec<_>>();
+
+        rams.sort_by_key(|ram| {
+            let other = runner_up(ram.index).map_or(0, |r| r.counter);
+            std::cmp::Reverse(ram.counter.saturating_sub(other))
+        });
+        rams.truncate(n);
+        rams
+    }
+}
+
+impl<F> Discriminator<F>
+where
+    F: CountingFilter,
+{
+    /// Returns the activation of every RAM for a given input sample.
+    pub fn explain<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> Vec<RamActivation>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_explain(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the activation of every RAM for a given input sample, or an
+    /// error if the sample size does not match the input size.
+    pub fn try_explain<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<RamActivation>>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let mapping = self.mapping();
+
+        if sample.len() != mapping.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: mapping.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        self.filters()
+            .iter()
+            .zip(mapping.addresses(sample.raw_bits()))
+            .enumerate()
+            .map(|(index, (filter, address))| {
+                Ok(RamActivation {
+                    index,
+                    inputs: mapping.tuple(index),
+                    address,
+                    counter: filter.try_counter(&address)?.unwrap_or(0),
+                    fired: filter.try_contains(&address)?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: CountingFilter,
+{
+    /// Returns the explanation of the model prediction for a given input
+    /// sample.
+    pub fn explain<T, O>(&self, sample: &Sample<L, T, O>) -> Explanation<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_explain(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the explanation of the model prediction for a given input
+    /// sample, or an error if the sample is invalid.
+    pub fn try_explain<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Explanation<L>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.explained_labels(sample).map(Explanation::new)
+    }
+
+    /// Returns the explanation of the model prediction for a given input
+    /// sample using a given [`Bleaching`](./enum.Bleaching.html) strategy,
+    /// where a RAM fires if its counter is above the bleaching threshold.
+    pub fn explain_bleaching<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> Explanation<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_explain_bleaching(sample, bleaching)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the explanation of the model prediction for a given input
+    /// sample using a given [`Bleaching`](./enum.Bleaching.html) strategy,
+    /// or an error if the sample is invalid.
+    pub fn try_explain_bleaching<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> WisardResult<Explanation<L>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let mut labels = self.explained_labels(sample)?;
+        let counters = labels
+            .iter()
+            .map(|explained| explained.rams.iter().map(|r| r.counter).collect())
+            .collect::<Vec<_>>();
+        let threshold = bleaching.threshold(&counters);
+
+        for ram in labels.iter_mut().flat_map(|l| l.rams.iter_mut()) {
+            ram.fired = ram.counter > threshold;
+        }
+
+        Ok(Explanation::new(labels))
+    }
+
+    /// Returns the score breakdown of every label, in the order of the
+    /// discriminators.
+    fn explained_labels<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<LabelExplanation<L>>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.discriminators()
+            .map(|(label, disc)| {
+                let rams = disc.try_explain(sample)?;
+                Ok(LabelExplanation {
+                    label: *label,
+                    rams,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bitvec::prelude::*;
+
+    use super::*;
+    use crate::{
+        filter::PackedLUTFilterBuilder,
+        model::{BinaryWisard, CountingWisard},
+    };
+
+    fn sample(bits: BitVec, label: usize) -> Sample<usize> {
+        Sample::from_raw_parts(bits, 1, label)
+    }
+
+    #[test]
+    fn discriminator_explain() {
+        let builder = PackedLUTFilterBuilder::new(2, 2, 0);
+        let mut disc = Discriminator::from_filter_builder(4, 2, &builder);
+        disc.fit(&sample(bitvec![1, 1, 0, 1], 0));
+        disc.fit(&sample(bitvec![1, 1, 1, 0], 0));
+
+        let rams = disc.explain(&sample(bitvec![1, 1, 0, 0], 0));
+        assert_eq!(rams.len(), 2);
+        assert_eq!(rams[0].inputs(), &[0, 1]);
+        assert_eq!(rams[0].address(), 0b11);
+        assert_eq!(rams[0].counter(), 2);
+        assert!(rams[0].fired());
+        assert_eq!(rams[1].inputs(), &[2, 3]);
+        assert_eq!(rams[1].counter(), 0);
+        assert!(!rams[1].fired());
+    }
+
+    #[test]
+    fn wisard_explain() {
+        let labels = HashSet::from_iter([0, 1, 2]);
+        let mut model = BinaryWisard::with_seed(8, 2, labels, [1; 32]);
+        model.fit(&sample(bitvec![1, 1, 1, 1, 0, 0, 0, 0], 0));
+        model.fit(&sample(bitvec![1, 1, 0, 0, 0, 0, 0, 0], 1));
+        model.fit(&sample(bitvec![0, 0, 0, 0, 1, 1, 1, 1], 2));
+
+        let input = sample(bitvec![1, 1, 1, 1, 0, 0, 0, 0], 0);
+        let explanation = model.explain(&input);
+        assert_eq!(explanation.labels().len(), 3);
+        assert_eq!(explanation.winner().unwrap().label(), &0);
+        assert_eq!(explanation.winner().unwrap().score(), 4);
+        assert_eq!(explanation.runner_up().unwrap().label(), &1);
+        assert_eq!(model.predict(&input), 0);
+
+        let scores = model.scores(&input);
+        for (score, label) in scores {
+            assert_eq!(explanation.get(&label).unwrap().score(), score);
+        }
+
+        let rams = explanation.differentiating_rams(4);
+        let runner_up = explanation.runner_up().unwrap();
+        assert!(!rams.is_empty());
+        assert!(rams.iter().all(|ram| ram.fired()));
+        assert!(rams
+            .iter()
+            .all(|ram| !runner_up.rams()[ram.index()].fired()));
+        assert_eq!(explanation.differentiating_rams(0).len(), 0);
+    }
+
+    #[test]
+    fn counting_wisard_explain() {
+        let labels = HashSet::from_iter([0, 1]);
+        let mut model = CountingWisard::with_seed(8, 2, 4, labels, [1; 32]);
+        let input = sample(bitvec![1, 1, 1, 1, 0, 0, 0, 0], 0);
+
+        for _ in 0..3 {
+            model.fit(&input);
+        }
+
+        model.fit(&sample(bitvec![1, 1, 1, 1, 0, 0, 0, 0], 1));
+        model.fit(&sample(bitvec![0, 0, 0, 0, 1, 1, 1, 1], 1));
+
+        let explanation = model.explain(&input);
+        assert_eq!(explanation.winner().unwrap().label(), &0);
+        assert_eq!(model.predict(&input), 0);
+
+        for (score, label) in model.scores(&input) {
+            assert_eq!(explanation.get(&label).unwrap().score(), score);
+        }
+
+        assert_eq!(explanation.winner().unwrap().score(), 4);
+        assert_eq!(explanation.runner_up().unwrap().score(), 0);
+    }
+
+    #[test]
+    fn explain_matches_prediction_ties() {
+        let labels = HashSet::from_iter(0..8);
+        let model = BinaryWisard::with_seed(8, 2, labels, [1; 32]);
+        let input = sample(bitvec![1, 0, 1, 0, 1, 0, 1, 0], 0);
+        let explanation = model.explain(&input);
+        assert_eq!(
+            explanation.winner().unwrap().label(),
+            &model.predict(&input)
+        );
+    }
+}
@@ -0,0 +1,643 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+use bitvec::prelude::*;
+
+use crate::{
+    filter::PackedLUTFilter,
+    model::{Bleaching, Discriminator, TupleMapping, WisardBase},
+    sample::Label,
+};
+
+/// The magic bytes at the beginning of every model file.
+pub const MAGIC: [u8; 4] = *b"WSRD";
+
+/// The latest version of the model file format.
+pub const VERSION: u16 = 2;
+
+/// The largest input size, in bits, accepted when reading a model file.
+const MAX_INPUT_SIZE: usize = 1 << 26;
+
+/// The largest lookup table size, in bits, accepted when reading a model
+/// file.
+const MAX_LUT_SIZE: usize = 1 << 30;
+
+pub type FormatResult<T> = Result<T, FormatError>;
+
+/// An error that occurs while reading or writing a model file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FormatError {
+    /// An I/O error from the underlying reader or writer.
+    IO(io::Error),
+    /// The file does not start with the expected magic bytes.
+    InvalidMagic([u8; 4]),
+    /// The file was written with an unknown version of the format.
+    UnsupportedVersion(u16),
+    /// The file contains a model of a different kind than requested.
+    UnexpectedKind {
+        expected: ModelKind,
+        found: ModelKind,
+    },
+    /// The header contains an invalid or inconsistent value.
+    InvalidHeader(&'static str),
+    /// A label from the label table could not be encoded or decoded.
+    InvalidLabel(bincode::Error),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "I/O error: {err}"),
+            Self::InvalidMagic(magic) => {
+                write!(f, "invalid magic bytes: {magic:?}")
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version: {version}")
+            }
+            Self::UnexpectedKind { expected, found } => write!(
+                f,
+                "expected a {expected:?} model, found a {found:?} model",
+            ),
+            Self::InvalidHeader(reason) => {
+                write!(f, "invalid model header: {reason}")
+            }
+            Self::InvalidLabel(err) => write!(f, "invalid label: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            Self::InvalidLabel(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+/// The kind of model stored in a model file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ModelKind {
+    /// A [`BinaryWisard`](./struct.BinaryWisard.html) model.
+    Binary,
+    /// A [`CountingWisard`](./struct.CountingWisard.html) model.
+    Counting,
+}
+
+impl ModelKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Binary => 0,
+            Self::Counting => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> FormatResult<Self> {
+        match byte {
+            0 => Ok(Self::Binary),
+            1 => Ok(Self::Counting),
+            _ => Err(FormatError::InvalidHeader("unknown model kind")),
+        }
+    }
+}
+
+/// The header of a model file, holding the model hyperparameters.
+///
+/// Every model file starts with the [`MAGIC`](./constant.MAGIC.html) bytes
+/// and a little-endian `u16` format version, followed by the hyperparameters,
+/// a table of bincode-encoded labels and the raw lookup table bits of every
+/// RAM, packed without any per-element overhead. The version is checked when
+/// reading, and headers written with older versions of the format are
+/// migrated to the latest one. The RAM tuple mapping is not stored, as it is
+/// derived from the seed.
+///
+/// Version 1 only stored binary models, so its header lacks the model kind,
+/// count size, threshold and bleaching strategy.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ModelHeader {
+    version: u16,
+    kind: ModelKind,
+    input_size: usize,
+    addr_size: usize,
+    count_size: usize,
+    threshold: usize,
+    bleaching: Bleaching,
+    seed: [u8; 32],
+}
+
+impl ModelHeader {
+    /// Creates a new [`ModelHeader`](./struct.ModelHeader.html) instance
+    /// using the latest format version.
+    pub(crate) fn new(
+        kind: ModelKind,
+        input_size: usize,
+        addr_size: usize,
+        count_size: usize,
+        threshold: usize,
+        bleaching: Bleaching,
+        seed: [u8; 32],
+    ) -> Self {
+        Self {
+            version: VERSION,
+            kind,
+            input_size,
+            addr_size,
+            count_size,
+            threshold,
+            bleaching,
+            seed,
+        }
+    }
+
+    /// Reads and validates a model header.
+    pub fn read_from<R: Read>(reader: &mut R) -> FormatResult<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(FormatError::InvalidMagic(magic));
+        }
+
+        let header = match read_u16(reader)? {
+            1 => HeaderV1::read_from(reader)?.migrate(),
+            2 => Self::read_v2(reader)?,
+            version => return Err(FormatError::UnsupportedVersion(version)),
+        };
+
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// Reads the version 2 header, which added counting models.
+    fn read_v2<R: Read>(reader: &mut R) -> FormatResult<Self> {
+        let kind = ModelKind::from_byte(read_u8(reader)?)?;
+        let input_size = read_usize(reader)?;
+        let addr_size = read_usize(reader)?;
+        let count_size = read_usize(reader)?;
+        let threshold = read_usize(reader)?;
+        let bleaching = match read_u8(reader)? {
+            0 => Bleaching::Linear,
+            1 => Bleaching::BinarySearch,
+            _ => return Err(FormatError::InvalidHeader("unknown bleaching")),
+        };
+        let mut seed = [0u8; 32];
+        reader.read_exact(&mut seed)?;
+        Ok(Self {
+            version: 2,
+            kind,
+            input_size,
+            addr_size,
+            count_size,
+            threshold,
+            bleaching,
+            seed,
+        })
+    }
+
+    /// Writes the header using the latest version of the format.
+    pub(crate) fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+    ) -> FormatResult<()> {
+        let bleaching = match self.bleaching {
+            Bleaching::Linear => 0u8,
+            Bleaching::BinarySearch => 1u8,
+        };
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&[self.kind.to_byte()])?;
+        write_usize(writer, self.input_size)?;
+        write_usize(writer, self.addr_size)?;
+        write_usize(writer, self.count_size)?;
+        write_usize(writer, self.threshold)?;
+        writer.write_all(&[bleaching])?;
+        writer.write_all(&self.seed)?;
+        Ok(())
+    }
+
+    fn validate(&self) -> FormatResult<()> {
+        if self.input_size == 0 || self.input_size > MAX_INPUT_SIZE {
+            return Err(FormatError::InvalidHeader("input size out of range"));
+        }
+
+        if self.addr_size == 0 {
+            return Err(FormatError::InvalidHeader(
+                "address size out of range",
+            ));
+        }
+
+        if self.count_size == 0 || self.count_size >= usize::BITS as usize {
+            return Err(FormatError::InvalidHeader("count size out of range"));
+        }
+
+        self.lut_size()?;
+
+        // A threshold at or above the largest counter value would reject
+        // every item, so it can only come from a corrupt file.
+        if self.threshold >= (1 << self.count_size) - 1 {
+            return Err(FormatError::InvalidHeader("threshold out of range"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of bits of each RAM lookup table.
+    fn lut_size(&self) -> FormatResult<usize> {
+        u32::try_from(self.addr_size)
+            .ok()
+            .and_then(|addr_size| 1usize.checked_shl(addr_size))
+            .and_then(|size| size.checked_mul(self.count_size))
+            .filter(|&size| size <= MAX_LUT_SIZE)
+            .ok_or(FormatError::InvalidHeader("lookup table size out of range"))
+    }
+
+    /// Returns the format version the model was written with.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the kind of the stored model.
+    pub fn kind(&self) -> ModelKind {
+        self.kind
+    }
+
+    /// Returns the model input size.
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    /// Returns the model address size.
+    pub fn addr_size(&self) -> usize {
+        self.addr_size
+    }
+
+    /// Returns the number of bits of each RAM counter.
+    pub fn count_size(&self) -> usize {
+        self.count_size
+    }
+
+    /// Returns the RAM membership threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns the bleaching strategy of the model.
+    pub fn bleaching(&self) -> Bleaching {
+        self.bleaching
+    }
+
+    /// Returns the permutation seed of the model.
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+}
+
+/// The version 1 header, which only supported binary models.
+struct HeaderV1 {
+    input_size: usize,
+    addr_size: usize,
+    seed: [u8; 32],
+}
+
+impl HeaderV1 {
+    fn read_from<R: Read>(reader: &mut R) -> FormatResult<Self> {
+        let input_size = read_usize(reader)?;
+        let addr_size = read_usize(reader)?;
+        let mut seed = [0u8; 32];
+        reader.read_exact(&mut seed)?;
+        Ok(Self {
+            input_size,
+            addr_size,
+            seed,
+        })
+    }
+
+    /// Migrates the header to the latest version, using the hyperparameters
+    /// of a binary model. The RAM contents are laid out the same way.
+    fn migrate(self) -> ModelHeader {
+        ModelHeader {
+            version: 1,
+            kind: ModelKind::Binary,
+            input_size: self.input_size,
+            addr_size: self.addr_size,
+            count_size: 1,
+            threshold: 0,
+            bleaching: Bleaching::default(),
+            seed: self.seed,
+        }
+    }
+}
+
+/// Writes a model header followed by its label table and RAM contents.
+pub(crate) fn write_model<L, W>(
+    mut writer: W,
+    header: &ModelHeader,
+    base: &WisardBase<L, PackedLUTFilter>,
+) -> FormatResult<()>
+where
+    L: Label,
+    W: Write,
+{
+    header.write_to(&mut writer)?;
+
+    let discs = base.discriminators().collect::<Vec<_>>();
+    write_usize(&mut writer, discs.len())?;
+
+    for (label, _) in discs.iter() {
+        let bytes =
+            bincode::serialize(label).map_err(FormatError::InvalidLabel)?;
+        write_usize(&mut writer, bytes.len())?;
+        writer.write_all(&bytes)?;
+    }
+
+    for (_, disc) in discs.iter() {
+        for filter in disc.filters() {
+            let lut = filter.raw_lut();
+            let mut bytes = vec![0u8; lut.len().div_ceil(8)];
+            bytes.view_bits_mut::<Lsb0>()[..lut.len()].clone_from_bitslice(lut);
+            writer.write_all(&bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a model header followed by its label table and RAM contents.
+pub(crate) fn read_model<L, R>(
+    mut reader: R,
+) -> FormatResult<(ModelHeader, WisardBase<L, PackedLUTFilter>)>
+where
+    L: Label,
+    R: Read,
+{
+    let header = ModelHeader::read_from(&mut reader)?;
+    let num_labels = read_usize(&mut reader)?;
+    let mut labels = Vec::new();
+
+    for _ in 0..num_labels {
+        let len = read_usize(&mut reader)?;
+        let bytes = read_bytes(&mut reader, len)?;
+        let label =
+            bincode::deserialize(&bytes).map_err(FormatError::InvalidLabel)?;
+        labels.push(label);
+    }
+
+    let num_filters = header.input_size.div_ceil(header.addr_size);
+    let lut_size = header.lut_size()?;
+    let mut filters_by_label = Vec::new();
+
+    // The vectors only grow as the RAM contents are read, so a corrupt header
+    // cannot make the reader allocate more memory than the file provides.
+    for label in labels {
+        let mut filters = Vec::new();
+
+        for _ in 0..num_filters {
+            let bytes = read_bytes(&mut reader, lut_size.div_ceil(8))?;
+            let mut lut = bitvec![usize, Lsb0; 0; lut_size];
+            lut.clone_from_bitslice(&bytes.view_bits::<Lsb0>()[..lut_size]);
+            filters.push(PackedLUTFilter::from_raw_parts(
+                header.addr_size,
+                header.count_size,
+                header.threshold,
+                lut,
+            ));
+        }
+
+        filters_by_label.push((label, filters));
+    }
+
+    let mut disc = HashMap::with_capacity(filters_by_label.len());
+    let mapping = Arc::new(TupleMapping::random(
+        header.input_size,
+        header.addr_size,
+        header.seed,
+    ));
+
+    for (label, filters) in filters_by_label {
+        let discriminator =
+            Discriminator::from_filters(mapping.clone(), filters);
+
+        if disc.insert(label, discriminator).is_some() {
+            return Err(FormatError::InvalidHeader("duplicated label"));
+        }
+    }
+
+    let base = WisardBase::from_discriminators(mapping, disc);
+
+    Ok((header, base))
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> FormatResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+
+    if bytes.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(bytes)
+}
+
+fn read_usize<R: Read>(reader: &mut R) -> FormatResult<usize> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|_| FormatError::InvalidHeader("value does not fit usize"))
+}
+
+fn write_usize<W: Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        model::{BinaryWisard, CountingWisard},
+        sample::Sample,
+    };
+
+    fn samples() -> Vec<Sample<u8>> {
+        vec![
+            (bitvec![1, 1, 1, 0, 0, 0, 0, 0, 1], 0),
+            (bitvec![1, 1, 1, 1, 0, 0, 0, 0, 1], 0),
+            (bitvec![0, 0, 0, 0, 1, 1, 1, 1, 0], 1),
+            (bitvec![0, 0, 0, 0, 0, 1, 1, 1, 0], 1),
+        ]
+        .into_iter()
+        .map(|(v, l)| Sample::from_raw_parts(v, 1, l))
+        .collect()
+    }
+
+    fn sorted(mut scores: Vec<(usize, u8)>) -> Vec<(usize, u8)> {
+        scores.sort_by_key(|(_, label)| *label);
+        scores
+    }
+
+    #[test]
+    fn binary_wisard_file_round_trip() {
+        let labels = HashSet::from_iter([0u8, 1]);
+        let mut model = BinaryWisard::new(9, 2, labels);
+
+        for sample in samples().iter() {
+            model.fit(sample);
+        }
+
+        let mut bytes = Vec::new();
+        model.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes[..4], MAGIC);
+
+        let loaded = BinaryWisard::<u8>::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.seed(), model.seed());
+
+        for sample in samples().iter() {
+            let expected = sorted(model.scores(sample));
+            assert_eq!(sorted(loaded.scores(sample)), expected);
+        }
+    }
+
+    #[test]
+    fn counting_wisard_file_round_trip() {
+        let labels = HashSet::from_iter([0u8, 1]);
+        let mut model = CountingWisard::new(9, 3, 4, labels);
+        model.set_bleaching(Bleaching::BinarySearch);
+
+        for sample in samples().iter().cycle().take(10) {
+            model.fit(sample);
+        }
+
+        let mut bytes = Vec::new();
+        model.write_to(&mut bytes).unwrap();
+
+        let header = ModelHeader::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header.kind(), ModelKind::Counting);
+        assert_eq!(header.count_size(), 4);
+
+        let loaded = CountingWisard::<u8>::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.bleaching(), Bleaching::BinarySearch);
+
+        for sample in samples().iter() {
+            let expected = sorted(model.scores(sample));
+            assert_eq!(sorted(loaded.scores(sample)), expected);
+        }
+
+        let err = BinaryWisard::<u8>::read_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(err, FormatError::UnexpectedKind { .. }));
+    }
+
+    #[test]
+    fn read_v1_model() {
+        #[rustfmt::skip]
+        let bytes = [
+            // The magic bytes and the format version.
+            b'W', b'S', b'R', b'D', 1, 0,
+            // The input size and the address size.
+            4, 0, 0, 0, 0, 0, 0, 0,
+            2, 0, 0, 0, 0, 0, 0, 0,
+            // The permutation seed.
+            7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+            7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+            // The label table, holding the labels 0 and 1.
+            2, 0, 0, 0, 0, 0, 0, 0,
+            1, 0, 0, 0, 0, 0, 0, 0, 0,
+            1, 0, 0, 0, 0, 0, 0, 0, 1,
+            // The RAMs of label 0 hold address 0, those of label 1 address 3.
+            0b0001, 0b0001,
+            0b1000, 0b1000,
+        ];
+
+        let header = ModelHeader::read_from(&mut &bytes[..]).unwrap();
+        assert_eq!(header.version(), 1);
+        assert_eq!(header.kind(), ModelKind::Binary);
+        assert_eq!(header.input_size(), 4);
+        assert_eq!(header.addr_size(), 2);
+        assert_eq!(header.count_size(), 1);
+        assert_eq!(header.threshold(), 0);
+
+        let loaded = BinaryWisard::<u8>::read_from(&bytes[..]).unwrap();
+        assert_eq!(loaded.seed(), [7u8; 32]);
+
+        let zeros = Sample::from_raw_parts(bitvec![0; 4], 1, 0u8);
+        let ones = Sample::from_raw_parts(bitvec![1; 4], 1, 1u8);
+        assert_eq!(sorted(loaded.scores(&zeros)), vec![(2, 0), (0, 1)]);
+        assert_eq!(sorted(loaded.scores(&ones)), vec![(0, 0), (2, 1)]);
+
+        let err = CountingWisard::<u8>::read_from(&bytes[..]).unwrap_err();
+        assert!(matches!(err, FormatError::UnexpectedKind { .. }));
+    }
+
+    #[test]
+    fn read_invalid_files() {
+        let err = ModelHeader::read_from(&mut &b"NOPE"[..]).unwrap_err();
+        assert!(matches!(err, FormatError::InvalidMagic(_)));
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&99u16.to_le_bytes());
+        let err = ModelHeader::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(err, FormatError::UnsupportedVersion(99)));
+
+        let labels = HashSet::from_iter([0u8, 1]);
+        let model = BinaryWisard::new(9, 2, labels);
+        let mut bytes = Vec::new();
+        model.write_to(&mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        let err = BinaryWisard::<u8>::read_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(err, FormatError::IO(_)));
+    }
+
+    #[test]
+    fn read_corrupt_headers() {
+        let labels = HashSet::from_iter([0u8, 1]);
+        let model = CountingWisard::new(9, 3, 4, labels);
+        let mut bytes = Vec::new();
+        model.write_to(&mut bytes).unwrap();
+
+        // Offsets of the input size, address size, count size and threshold.
+        let corrupt = |offset: usize, value: u64| {
+            let mut bytes = bytes.clone();
+            bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+            CountingWisard::<u8>::read_from(bytes.as_slice()).unwrap_err()
+        };
+
+        let err = corrupt(7, u64::MAX >> 1);
+        assert!(matches!(err, FormatError::InvalidHeader(_)));
+        let err = corrupt(15, 32);
+        assert!(matches!(err, FormatError::InvalidHeader(_)));
+        let err = corrupt(15, 200);
+        assert!(matches!(err, FormatError::InvalidHeader(_)));
+        let err = corrupt(23, 64);
+        assert!(matches!(err, FormatError::InvalidHeader(_)));
+        let err = corrupt(31, 15);
+        assert!(matches!(err, FormatError::InvalidHeader(_)));
+
+        // A valid header whose RAM contents are missing fails while reading.
+        let err = corrupt(15, 24);
+        assert!(matches!(err, FormatError::IO(_)));
+    }
+}
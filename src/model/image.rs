@@ -0,0 +1,233 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{WisardError, WisardResult},
+    filter::CountingFilter,
+    model::{Discriminator, WisardBase},
+    sample::Label,
+};
+
+/// The largest tuple size supported by mental images, as every address of
+/// every RAM is visited.
+pub const MAX_IMAGE_ADDR_SIZE: usize = 24;
+
+/// A DRASiW mental image of a discriminator, holding an activation count
+/// for each input bit.
+///
+/// The activation of an input bit is the sum of the counters of every RAM
+/// position whose address has that bit set, reconstructed through the
+/// discriminator mapping. Highly activated bits are the input regions the
+/// discriminator relies on the most.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct MentalImage {
+    values: Vec<usize>,
+}
+
+impl MentalImage {
+    /// Returns the activation count of each input bit.
+    pub fn values(&self) -> &[usize] {
+        &self.values
+    }
+
+    /// Returns the number of input bits.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the image has no input bits.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the largest activation count, or zero if the image is empty.
+    pub fn max(&self) -> usize {
+        self.values.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the rows of the image for a given `width`, where the last row
+    /// is padded with zeros.
+    fn rows(&self, width: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        assert!(width > 0, "image width must not be zero");
+        self.values.chunks(width).map(move |row| {
+            let mut row = row.to_vec();
+            row.resize(width, 0);
+            row
+        })
+    }
+
+    /// Writes the image as a binary (P5) PGM file with `width` columns, where
+    /// the activation counts are scaled to grayscale levels between zero and
+    /// 255. The last row is padded with zeros.
+    pub fn write_pgm<W: Write>(
+        &self,
+        mut writer: W,
+        width: usize,
+    ) -> io::Result<()> {
+        let rows = self.rows(width);
+        let height = self.len().div_ceil(width);
+        let max = self.max().max(1) as u128;
+        write!(writer, "P5\n{width} {height}\n255\n")?;
+
+        for row in rows {
+            let bytes = row
+                .into_iter()
+                .map(|value| (value as u128 * 255 / max) as u8)
+                .collect::<Vec<_>>();
+            writer.write_all(&bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the raw activation counts as CSV, with `width` values per row.
+    /// The last row is padded with zeros.
+    pub fn write_csv<W: Write>(
+        &self,
+        mut writer: W,
+        width: usize,
+    ) -> io::Result<()> {
+        for row in self.rows(width) {
+            let row = row.iter().map(usize::to_string).collect::<Vec<_>>();
+            writeln!(writer, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F> Discriminator<F>
+where
+    F: CountingFilter,
+{
+    /// Returns the mental image of the discriminator.
+    ///
+    /// Every address of every RAM is visited, so this is only practical for
+    /// small address sizes. Activation counts saturate at `usize::MAX`.
+    pub fn mental_image(&self) -> MentalImage {
+        self.try_mental_image()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the mental image of the discriminator, or an error if the
+    /// RAM counters cannot be read or a tuple is larger than
+    /// [`MAX_IMAGE_ADDR_SIZE`](./constant.MAX_IMAGE_ADDR_SIZE.html).
+    pub fn try_mental_image(&self) -> WisardResult<MentalImage> {
+        let mapping = self.mapping();
+        let mut values = vec![0usize; mapping.input_size()];
+
+        for (index, filter) in self.filters().iter().enumerate() {
+            let tuple = mapping.tuple(index);
+
+            if tuple.len() > MAX_IMAGE_ADDR_SIZE {
+                return Err(WisardError::InvalidMapping(
+                    "tuple too large for a mental image",
+                ));
+            }
+
+            for addr in 0..1usize << tuple.len() {
+                let count = filter.try_counter(&addr)?.unwrap_or(0);
+
+                if count == 0 {
+                    continue;
+                }
+
+                for (j, &bit) in tuple.iter().enumerate() {
+                    if addr >> j & 1 == 1 {
+                        values[bit] = values[bit].saturating_add(count);
+                    }
+                }
+            }
+        }
+
+        Ok(MentalImage { values })
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: CountingFilter,
+{
+    /// Returns the mental image of the discriminator of each label.
+    pub fn mental_images(&self) -> HashMap<L, MentalImage> {
+        self.try_mental_images()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the mental image of the discriminator of each label, or an
+    /// error if the RAM counters cannot be read.
+    pub fn try_mental_images(&self) -> WisardResult<HashMap<L, MentalImage>> {
+        self.discriminators()
+            .map(|(label, disc)| Ok((*label, disc.try_mental_image()?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bitvec::prelude::*;
+
+    use super::*;
+    use crate::{
+        filter::PackedLUTFilterBuilder,
+        model::{CountingWisard, TupleMapping},
+        sample::Sample,
+    };
+
+    #[test]
+    fn discriminator_mental_image() {
+        let builder = PackedLUTFilterBuilder::new(2, 4, 0);
+        let mut disc = Discriminator::from_filter_builder(6, 2, &builder);
+        disc.fit(&Sample::from_raw_parts(bitvec![1, 1, 0, 0, 1, 0], 1, 0));
+        disc.fit(&Sample::from_raw_parts(bitvec![1, 0, 0, 0, 1, 1], 1, 0));
+        assert_eq!(disc.mental_image().values(), &[2, 1, 0, 0, 2, 1]);
+    }
+
+    #[test]
+    fn mental_image_large_tuples() {
+        let builder = PackedLUTFilterBuilder::new(2, 4, 0);
+        let tuples = vec![(0..usize::BITS as usize).collect()];
+        let mapping = TupleMapping::from_indices(64, tuples).unwrap();
+        let disc = Discriminator::from_mapping(mapping, &builder);
+        assert!(matches!(
+            disc.try_mental_image(),
+            Err(WisardError::InvalidMapping(_)),
+        ));
+    }
+
+    #[test]
+    fn wisard_mental_images() {
+        let labels = HashSet::from_iter([0, 1]);
+        let mut model = CountingWisard::with_seed(4, 2, 4, labels, [1; 32]);
+        model.fit(&Sample::from_raw_parts(bitvec![1, 1, 0, 0], 1, 0));
+        model.fit(&Sample::from_raw_parts(bitvec![1, 1, 0, 0], 1, 0));
+        model.fit(&Sample::from_raw_parts(bitvec![0, 0, 1, 0], 1, 1));
+
+        let images = model.mental_images();
+        assert_eq!(images[&0].values(), &[2, 2, 0, 0]);
+        assert_eq!(images[&1].values(), &[0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn mental_image_export() {
+        let image = MentalImage {
+            values: vec![0, 2, 4, 1, 3],
+        };
+
+        let mut pgm = Vec::new();
+        image.write_pgm(&mut pgm, 2).unwrap();
+        assert_eq!(&pgm[..11], b"P5\n2 3\n255\n");
+        assert_eq!(&pgm[11..], &[0, 127, 255, 63, 191, 0]);
+
+        let mut csv = Vec::new();
+        image.write_csv(&mut csv, 3).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "0,2,4\n1,3,0\n");
+    }
+}
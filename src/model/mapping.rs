@@ -0,0 +1,346 @@
+use bitvec::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encode::Permute,
+    error::{WisardError, WisardResult},
+};
+
+/// The internal representation of the tuples of a mapping.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+enum Tuples {
+    /// Tuples of `addr_size` consecutive bits, starting every `stride` bits.
+    Strided { addr_size: usize, stride: usize },
+    /// Tuples given by explicit lists of input bit indices.
+    Indexed(Vec<Vec<usize>>),
+}
+
+/// A mapping from the input bits to the address bits of each RAM.
+///
+/// Each RAM is fed by a tuple of input bit indices, where the `j`-th index
+/// of a tuple selects the input bit used as the `j`-th bit of the address.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TupleMapping {
+    input_size: usize,
+    addr_size: usize,
+    num_tuples: usize,
+    tuples: Tuples,
+}
+
+impl TupleMapping {
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` contiguous input bits. The last
+    /// tuple is shorter if `input_size` is not a multiple of `addr_size`.
+    pub fn contiguous(input_size: usize, addr_size: usize) -> Self {
+        Self::try_contiguous(input_size, addr_size)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` contiguous input bits.
+    ///
+    /// Returns an error if `addr_size` is zero or larger than the number of
+    /// bits of a `usize`.
+    pub fn try_contiguous(
+        input_size: usize,
+        addr_size: usize,
+    ) -> WisardResult<Self> {
+        Self::try_overlapping(input_size, addr_size, addr_size)
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` contiguous input bits, with a new
+    /// tuple starting every `stride` bits. Tuples overlap when `stride` is
+    /// smaller than `addr_size` and skip input bits when it is larger. When
+    /// `stride` is at most `addr_size`, the last tuple ends at the last input
+    /// bit; otherwise, every tuple starts within the input bits.
+    pub fn overlapping(
+        input_size: usize,
+        addr_size: usize,
+        stride: usize,
+    ) -> Self {
+        Self::try_overlapping(input_size, addr_size, stride)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` contiguous input bits, with a new
+    /// tuple starting every `stride` bits.
+    ///
+    /// Returns an error if `addr_size` is zero or larger than the number of
+    /// bits of a `usize`, or if `stride` is zero.
+    pub fn try_overlapping(
+        input_size: usize,
+        addr_size: usize,
+        stride: usize,
+    ) -> WisardResult<Self> {
+        check_addr_size(addr_size)?;
+
+        if stride == 0 {
+            return Err(WisardError::InvalidMapping("stride is zero"));
+        }
+
+        let num_tuples = match input_size {
+            0 => 0,
+            _ => (input_size.saturating_sub(addr_size).div_ceil(stride) + 1)
+                .min(input_size.div_ceil(stride)),
+        };
+
+        Ok(Self {
+            input_size,
+            addr_size,
+            num_tuples,
+            tuples: Tuples::Strided { addr_size, stride },
+        })
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` input bits chosen by a random
+    /// permutation of the inputs, generated from a given `seed`.
+    ///
+    /// This is equivalent to encoding the samples with a
+    /// [`Permute`](../encode/struct.Permute.html) encoder using the same
+    /// `seed` and then using a [`contiguous`](#method.contiguous) mapping.
+    pub fn random(input_size: usize, addr_size: usize, seed: [u8; 32]) -> Self {
+        Self::try_random(input_size, addr_size, seed)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` input bits chosen by a random
+    /// permutation of the inputs, generated from a given `seed`.
+    ///
+    /// Returns an error if `addr_size` is zero or larger than the number of
+    /// bits of a `usize`.
+    pub fn try_random(
+        input_size: usize,
+        addr_size: usize,
+        seed: [u8; 32],
+    ) -> WisardResult<Self> {
+        check_addr_size(addr_size)?;
+        let permute = <Permute>::with_seed_and_size(seed, input_size);
+        Self::try_from_permutation(permute.table(), addr_size)
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` input bits taken in order from a
+    /// permutation `table` of the inputs.
+    pub fn from_permutation(table: &[usize], addr_size: usize) -> Self {
+        Self::try_from_permutation(table, addr_size)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// where each RAM is fed by `addr_size` input bits taken in order from a
+    /// permutation `table` of the inputs.
+    ///
+    /// Returns an error if `addr_size` is zero or larger than the number of
+    /// bits of a `usize`.
+    pub fn try_from_permutation(
+        table: &[usize],
+        addr_size: usize,
+    ) -> WisardResult<Self> {
+        check_addr_size(addr_size)?;
+        let tuples = table.chunks(addr_size).map(|c| c.to_vec()).collect();
+        Ok(Self::from_tuples(table.len(), tuples))
+    }
+
+    /// Creates a new [`TupleMapping`](./struct.TupleMapping.html) instance
+    /// from explicit lists of input bit indices, one for each RAM.
+    ///
+    /// Returns an error if any tuple is empty, longer than the number of
+    /// bits of a `usize`, or contains an index out of the input bounds.
+    pub fn from_indices(
+        input_size: usize,
+        tuples: Vec<Vec<usize>>,
+    ) -> WisardResult<Self> {
+        for tuple in tuples.iter() {
+            check_addr_size(tuple.len())?;
+
+            if tuple.iter().any(|&i| i >= input_size) {
+                return Err(WisardError::InvalidMapping(
+                    "tuple index out of the input bounds",
+                ));
+            }
+        }
+
+        Ok(Self::from_tuples(input_size, tuples))
+    }
+
+    fn from_tuples(input_size: usize, tuples: Vec<Vec<usize>>) -> Self {
+        Self {
+            input_size,
+            addr_size: tuples.iter().map(Vec::len).max().unwrap_or(0),
+            num_tuples: tuples.len(),
+            tuples: Tuples::Indexed(tuples),
+        }
+    }
+
+    /// Returns the number of input bits.
+    pub fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    /// Returns the size of the largest tuple, i.e. the RAM address size.
+    pub fn addr_size(&self) -> usize {
+        self.addr_size
+    }
+
+    /// Returns the number of tuples (RAMs).
+    pub fn len(&self) -> usize {
+        self.num_tuples
+    }
+
+    /// Returns `true` if the mapping has no tuples.
+    pub fn is_empty(&self) -> bool {
+        self.num_tuples == 0
+    }
+
+    /// Returns the input bit indices that feed the `index`-th RAM.
+    pub fn tuple(&self, index: usize) -> Vec<usize> {
+        match &self.tuples {
+            Tuples::Strided { addr_size, stride } => {
+                let start = index * stride;
+                (start..self.input_size.min(start + addr_size)).collect()
+            }
+            Tuples::Indexed(tuples) => tuples[index].clone(),
+        }
+    }
+
+    /// Returns the address of the `index`-th RAM for the given input bits.
+    pub fn address<T, O>(&self, bits: &BitSlice<T, O>, index: usize) -> usize
+    where
+        T: BitStore,
+        O: BitOrder,
+    {
+        let mut addr = 0usize;
+
+        match &self.tuples {
+            Tuples::Strided { addr_size, stride } => {
+                let start = index * stride;
+                let end = self.input_size.min(start + addr_size);
+                addr.view_bits_mut::<Lsb0>()[..end - start]
+                    .clone_from_bitslice(&bits[start..end]);
+            }
+            Tuples::Indexed(tuples) => {
+                for (j, &i) in tuples[index].iter().enumerate() {
+                    addr |= (bits[i] as usize) << j;
+                }
+            }
+        }
+
+        addr
+    }
+
+    /// Returns an iterator over the addresses of every RAM for the given
+    /// input bits.
+    pub fn addresses<'a, T, O>(
+        &'a self,
+        bits: &'a BitSlice<T, O>,
+    ) -> impl Iterator<Item = usize> + 'a
+    where
+        T: BitStore,
+        O: BitOrder,
+    {
+        (0..self.num_tuples).map(move |i| self.address(bits, i))
+    }
+}
+
+/// Returns an error if a tuple of `addr_size` bits cannot address a RAM.
+fn check_addr_size(addr_size: usize) -> WisardResult<()> {
+    if addr_size == 0 || addr_size > usize::BITS as usize {
+        return Err(WisardError::InvalidMapping("tuple size out of range"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_tuples(mapping: &TupleMapping) -> Vec<Vec<usize>> {
+        (0..mapping.len()).map(|i| mapping.tuple(i)).collect()
+    }
+
+    #[test]
+    fn contiguous_mapping() {
+        let mapping = TupleMapping::contiguous(5, 2);
+        let expected = vec![vec![0, 1], vec![2, 3], vec![4]];
+        assert_eq!(all_tuples(&mapping), expected);
+        let bits = bits![1, 0, 1, 1, 1];
+        let addrs = mapping.addresses(bits).collect::<Vec<_>>();
+        assert_eq!(addrs, vec![0b01, 0b11, 0b1]);
+    }
+
+    #[test]
+    fn overlapping_mapping() {
+        let mapping = TupleMapping::overlapping(5, 3, 1);
+        let expected = vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4]];
+        assert_eq!(all_tuples(&mapping), expected);
+        let mapping = TupleMapping::overlapping(6, 3, 2);
+        let expected = vec![vec![0, 1, 2], vec![2, 3, 4], vec![4, 5]];
+        assert_eq!(all_tuples(&mapping), expected);
+    }
+
+    #[test]
+    fn strided_mapping() {
+        let mapping = TupleMapping::overlapping(6, 2, 3);
+        let expected = vec![vec![0, 1], vec![3, 4]];
+        assert_eq!(all_tuples(&mapping), expected);
+        let mapping = TupleMapping::overlapping(7, 2, 3);
+        let expected = vec![vec![0, 1], vec![3, 4], vec![6]];
+        assert_eq!(all_tuples(&mapping), expected);
+    }
+
+    #[test]
+    fn random_mapping() {
+        let mapping = TupleMapping::random(7, 3, [1u8; 32]);
+        let mut indices = all_tuples(&mapping).concat();
+        assert_eq!(mapping.len(), 3);
+        indices.sort_unstable();
+        assert_eq!(indices, (0..7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn indexed_mapping() {
+        let tuples = vec![vec![3, 0], vec![1, 2, 1]];
+        let mapping = TupleMapping::from_indices(4, tuples).unwrap();
+        assert_eq!(mapping.addr_size(), 3);
+        let bits = bits![0, 1, 0, 1];
+        let addrs = mapping.addresses(bits).collect::<Vec<_>>();
+        assert_eq!(addrs, vec![0b01, 0b101]);
+        assert!(TupleMapping::from_indices(4, vec![vec![4]]).is_err());
+        assert!(TupleMapping::from_indices(4, vec![vec![]]).is_err());
+    }
+
+    #[test]
+    fn invalid_mapping() {
+        let too_large = usize::BITS as usize + 1;
+        assert!(TupleMapping::try_contiguous(128, 0).is_err());
+        assert!(TupleMapping::try_contiguous(128, too_large).is_err());
+        assert!(TupleMapping::try_overlapping(128, 2, 0).is_err());
+        assert!(TupleMapping::try_random(128, too_large, [0; 32]).is_err());
+        assert!(TupleMapping::try_from_permutation(&[0, 1], 0).is_err());
+        let mapping = TupleMapping::try_contiguous(128, 64).unwrap();
+        assert_eq!(mapping.len(), 2);
+    }
+
+    #[test]
+    fn random_mapping_matches_permute() {
+        use crate::{encode::SampleEncoder, sample::Sample};
+
+        let seed = [3u8; 32];
+        let sample = Sample::from_raw_parts(
+            bitvec![0, 1, 1, 0, 1, 0, 0, 1, 1, 1],
+            1,
+            0usize,
+        );
+        let mapping = TupleMapping::random(sample.len(), 3, seed);
+        let contiguous = TupleMapping::contiguous(sample.len(), 3);
+        let permuted = <Permute>::with_seed(seed).encode(sample.clone());
+        let expected = contiguous.addresses(permuted.raw_bits());
+        let found = mapping.addresses(sample.raw_bits());
+        assert!(expected.eq(found));
+    }
+}
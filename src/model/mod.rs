@@ -1,5 +1,23 @@
+mod bleach;
+mod classifier;
+mod clus;
 mod disc;
+mod explain;
+mod format;
+mod image;
+mod mapping;
+mod ranking;
+mod regression;
 mod wisard;
 
 pub use self::wisard::*;
+pub use bleach::*;
+pub use classifier::*;
+pub use clus::*;
 pub use disc::*;
+pub use explain::*;
+pub use format::*;
+pub use image::*;
+pub use mapping::*;
+pub use ranking::*;
+pub use regression::*;
@@ -0,0 +1,239 @@
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::WisardResult,
+    filter::Filter,
+    model::{sort_by_score, WisardBase},
+    sample::{Label, Sample},
+};
+
+/// The model scores of a sample, sorted from the highest score to the
+/// lowest.
+///
+/// Ties are ordered like the model predictions, from the smallest label to
+/// the largest, so the first label is always the predicted one. Scores are normalized by the number of RAMs,
+/// which is the highest possible score, into values between zero and one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Ranking<L: Label> {
+    scores: Vec<(usize, L)>,
+    rams: usize,
+}
+
+impl<L: Label> Ranking<L> {
+    /// Creates a new [`Ranking`](./struct.Ranking.html) instance from the
+    /// model scores of a sample and the number of RAMs of each discriminator.
+    pub fn new(mut scores: Vec<(usize, L)>, rams: usize) -> Self {
+        sort_by_score(&mut scores, |&score| score);
+        Self { scores, rams }
+    }
+
+    /// Returns the number of RAMs of each discriminator.
+    pub fn rams(&self) -> usize {
+        self.rams
+    }
+
+    /// Returns every score, from the highest to the lowest.
+    pub fn scores(&self) -> &[(usize, L)] {
+        &self.scores
+    }
+
+    /// Returns the `k` highest scores, from the highest to the lowest.
+    pub fn top_k(&self, k: usize) -> &[(usize, L)] {
+        &self.scores[..k.min(self.scores.len())]
+    }
+
+    /// Returns the predicted label, or `None` if there are no labels.
+    pub fn best(&self) -> Option<&L> {
+        self.scores.first().map(|(_, label)| label)
+    }
+
+    /// Returns the difference between the best and the second best scores,
+    /// or `None` if there are no labels. A missing second best score counts
+    /// as zero.
+    pub fn margin(&self) -> Option<usize> {
+        let (best, _) = self.scores.first()?;
+        let second = self.scores.get(1).map_or(0, |&(score, _)| score);
+        Some(best - second)
+    }
+
+    /// Returns the margin normalized by the number of RAMs.
+    pub fn normalized_margin(&self) -> Option<f64> {
+        self.margin().map(|margin| self.normalize(margin))
+    }
+
+    /// Returns every score normalized by the number of RAMs, from the
+    /// highest to the lowest.
+    pub fn normalized(&self) -> Vec<(f64, L)> {
+        self.scores
+            .iter()
+            .map(|&(score, label)| (self.normalize(score), label))
+            .collect()
+    }
+
+    /// Returns the softmax of the normalized scores with a given
+    /// `temperature`, from the highest to the lowest. The confidences add up
+    /// to one, and lower temperatures favor the best label.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `temperature` is not positive.
+    pub fn confidences(&self, temperature: f64) -> Vec<(f64, L)> {
+        assert!(temperature > 0.0, "temperature must be positive");
+
+        let normalized = self.normalized();
+        let max = normalized.first().map_or(0.0, |&(score, _)| score);
+        let exps = normalized
+            .into_iter()
+            .map(|(score, label)| (((score - max) / temperature).exp(), label))
+            .collect::<Vec<_>>();
+        let sum = exps.iter().map(|&(exp, _)| exp).sum::<f64>();
+
+        exps.into_iter()
+            .map(|(exp, label)| (exp / sum, label))
+            .collect()
+    }
+
+    /// Returns the softmax confidence of the predicted label with a given
+    /// `temperature`, or `None` if there are no labels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `temperature` is not positive.
+    pub fn confidence(&self, temperature: f64) -> Option<f64> {
+        self.confidences(temperature)
+            .first()
+            .map(|&(confidence, _)| confidence)
+    }
+
+    /// Normalizes a score by the number of RAMs.
+    fn normalize(&self, score: usize) -> f64 {
+        match self.rams {
+            0 => 0.0,
+            rams => score as f64 / rams as f64,
+        }
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: Filter,
+{
+    /// Returns the sorted model scores for a given input sample.
+    pub fn ranking<T, O>(&self, sample: &Sample<L, T, O>) -> Ranking<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_ranking(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the sorted model scores for a given input sample, or an error
+    /// if the sample is invalid.
+    pub fn try_ranking<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Ranking<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        Ok(Ranking::new(self.try_scores(sample)?, self.mapping().len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bitvec::prelude::*;
+
+    use super::*;
+    use crate::model::{BinaryWisard, CountingWisard};
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-9, "{left} != {right}");
+    }
+
+    #[test]
+    fn ranking_stats() {
+        let ranking = Ranking::new(vec![(1, 'a'), (4, 'b'), (2, 'c')], 4);
+        assert_eq!(ranking.scores(), &[(4, 'b'), (2, 'c'), (1, 'a')]);
+        assert_eq!(ranking.top_k(2), &[(4, 'b'), (2, 'c')]);
+        assert_eq!(ranking.top_k(8).len(), 3);
+        assert_eq!(ranking.best(), Some(&'b'));
+        assert_eq!(ranking.margin(), Some(2));
+        assert_close(ranking.normalized_margin().unwrap(), 0.5);
+        assert_eq!(ranking.normalized()[1], (0.5, 'c'));
+
+        let confidences = ranking.confidences(1.0);
+        let sum = confidences.iter().map(|&(c, _)| c).sum::<f64>();
+        assert_close(sum, 1.0);
+        let expected = 1.0 / (1.0 + (-1f64).exp() + (-1.5f64).exp());
+        assert_close(ranking.confidence(0.5).unwrap(), expected);
+        assert!(ranking.confidence(0.1) > ranking.confidence(1.0));
+
+        let empty = Ranking::<char>::new(Vec::new(), 4);
+        assert_eq!(empty.best(), None);
+        assert_eq!(empty.margin(), None);
+        assert_eq!(empty.confidence(1.0), None);
+    }
+
+    #[test]
+    fn ranking_matches_prediction() {
+        let labels = HashSet::from_iter(0..8);
+        let mut model = BinaryWisard::with_seed(8, 2, labels, [1; 32]);
+        let input =
+            Sample::from_raw_parts(bitvec![1, 1, 0, 0, 1, 0, 1, 0], 1, 3);
+
+        let ranking = model.ranking(&input);
+        assert_eq!(ranking.best(), Some(&model.predict(&input)));
+        assert_eq!(ranking.margin(), Some(0));
+
+        model.fit(&input);
+        let ranking = model.ranking(&input);
+        assert_eq!(ranking.rams(), 4);
+        assert_eq!(ranking.scores()[0], (4, 3));
+        assert_eq!(ranking.margin(), Some(4));
+        assert_close(ranking.normalized_margin().unwrap(), 1.0);
+
+        let labels = HashSet::from_iter([0, 1]);
+        let mut model = CountingWisard::with_seed(4, 2, 4, labels, [1; 32]);
+        let input = Sample::from_raw_parts(bitvec![1, 1, 0, 0], 1, 1);
+        model.fit(&input);
+        assert_eq!(model.ranking(&input).scores(), &[(2, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn ranking_ties() {
+        let scores = vec![(2, 'c'), (4, 'd'), (2, 'a'), (4, 'b'), (2, 'e')];
+        let ranking = Ranking::new(scores.clone(), 4);
+        assert_eq!(ranking.top_k(3), &[(4, 'b'), (4, 'd'), (2, 'a')]);
+        let reversed = scores.into_iter().rev().collect();
+        assert_eq!(Ranking::new(reversed, 4).scores(), ranking.scores());
+
+        // Untrained models tie every label, whatever the labels order.
+        let input = Sample::from_raw_parts(bitvec![1, 0, 1, 0], 1, 0);
+
+        for seed in 0..8 {
+            let labels = HashSet::from_iter(0..8);
+            let model = BinaryWisard::with_seed(4, 2, labels, [seed; 32]);
+            let ranking = model.ranking(&input);
+            assert_eq!(ranking.best(), Some(&0));
+            assert_eq!(model.predict(&input), 0);
+            let labels = ranking.top_k(8).iter().map(|&(_, label)| label);
+            assert!(labels.eq(0..8));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "temperature must be positive")]
+    fn ranking_zero_temperature() {
+        Ranking::new(vec![(1, 0)], 1).confidence(0.0);
+    }
+}
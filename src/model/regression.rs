@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use bitvec::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::{WisardError, WisardResult},
+    model::TupleMapping,
+    sample::{Label, Sample},
+};
+
+/// The strategy used to aggregate the estimates of the RAMs of a
+/// [`RegressionWisard`](./struct.RegressionWisard.html) into a prediction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Aggregation {
+    /// The arithmetic mean of the estimates.
+    #[default]
+    Mean,
+    /// The median of the estimates.
+    Median,
+    /// The power mean of the estimates with a given exponent, where an
+    /// exponent of zero corresponds to the geometric mean. Non-integer
+    /// exponents require positive estimates.
+    PowerMean(f64),
+}
+
+impl Aggregation {
+    /// Aggregates a non-empty set of estimates into a single value.
+    fn aggregate(&self, estimates: &mut [f64]) -> f64 {
+        let len = estimates.len() as f64;
+
+        match *self {
+            Self::Mean => estimates.iter().sum::<f64>() / len,
+            Self::Median => {
+                estimates.sort_unstable_by(f64::total_cmp);
+                let mid = estimates.len() / 2;
+
+                match estimates.len() % 2 {
+                    0 => (estimates[mid - 1] + estimates[mid]) / 2.0,
+                    _ => estimates[mid],
+                }
+            }
+            Self::PowerMean(0.0) => {
+                (estimates.iter().map(|e| e.ln()).sum::<f64>() / len).exp()
+            }
+            Self::PowerMean(p) => {
+                (estimates.iter().map(|e| e.powf(p)).sum::<f64>() / len)
+                    .powf(p.recip())
+            }
+        }
+    }
+}
+
+/// A regression WiSARD (ReW) model, which predicts real-valued targets.
+///
+/// Each RAM position stores the number of samples that addressed it during
+/// training and the sum of their targets. The estimate of a RAM is the mean
+/// target of its addressed position, and the prediction aggregates the
+/// estimates of every RAM whose addressed position was trained, using the
+/// configured [`Aggregation`](./enum.Aggregation.html).
+///
+/// The RAM positions are stored sparsely, so only trained positions use
+/// memory. Sample labels are ignored by this model.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegressionWisard {
+    mapping: TupleMapping,
+    rams: Vec<HashMap<usize, (usize, f64)>>,
+    aggregation: Aggregation,
+}
+
+impl RegressionWisard {
+    /// Creates a new [`RegressionWisard`](./struct.RegressionWisard.html)
+    /// instance using `rand::random()` as the permutation seed.
+    ///
+    /// The `input_size` value determines the total number of input bits.
+    /// The `addr_size` value corresponds to the address size of the RAMs.
+    pub fn new(input_size: usize, addr_size: usize) -> Self {
+        Self::with_seed(input_size, addr_size, rand::random())
+    }
+
+    /// Creates a new [`RegressionWisard`](./struct.RegressionWisard.html)
+    /// instance using a given permutation seed.
+    ///
+    /// The `input_size` value determines the total number of input bits.
+    /// The `addr_size` value corresponds to the address size of the RAMs.
+    /// The `seed` value determines the permutation seed.
+    pub fn with_seed(
+        input_size: usize,
+        addr_size: usize,
+        seed: [u8; 32],
+    ) -> Self {
+        Self::try_with_seed(input_size, addr_size, seed)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`RegressionWisard`](./struct.RegressionWisard.html)
+    /// instance using a given permutation seed, or returns an error if
+    /// `addr_size` is out of range.
+    pub fn try_with_seed(
+        input_size: usize,
+        addr_size: usize,
+        seed: [u8; 32],
+    ) -> WisardResult<Self> {
+        let mapping = TupleMapping::try_random(input_size, addr_size, seed)?;
+        Ok(Self::from_mapping(mapping))
+    }
+
+    /// Creates a new [`RegressionWisard`](./struct.RegressionWisard.html)
+    /// instance using a given [`TupleMapping`](./struct.TupleMapping.html),
+    /// which determines which input bits feed each RAM.
+    pub fn from_mapping(mapping: TupleMapping) -> Self {
+        let rams = vec![HashMap::new(); mapping.len()];
+        Self {
+            mapping,
+            rams,
+            aggregation: Aggregation::default(),
+        }
+    }
+
+    /// Sets the aggregation strategy used for predictions.
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Returns the model input size.
+    pub fn input_size(&self) -> usize {
+        self.mapping.input_size()
+    }
+
+    /// Returns the model address size.
+    pub fn addr_size(&self) -> usize {
+        self.mapping.addr_size()
+    }
+
+    /// Returns the tuple mapping of the RAMs.
+    pub fn mapping(&self) -> &TupleMapping {
+        &self.mapping
+    }
+
+    /// Returns the aggregation strategy used for predictions.
+    pub fn aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
+
+    /// Changes the aggregation strategy used for predictions.
+    pub fn set_aggregation(&mut self, aggregation: Aggregation) {
+        self.aggregation = aggregation;
+    }
+
+    /// Returns the RAM addresses of a given input sample, or an error if the
+    /// sample size does not match the input size.
+    fn addresses<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<usize>>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        if sample.len() != self.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: self.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        Ok(self.mapping.addresses(sample.raw_bits()).collect())
+    }
+
+    /// Fits (trains) the model with a given input sample and its `target`.
+    pub fn fit<L, T, O>(&mut self, sample: &Sample<L, T, O>, target: f64)
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_fit(sample, target)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a given input sample and its `target`,
+    /// returning an error if the sample size does not match the input size.
+    pub fn try_fit<L, T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+        target: f64,
+    ) -> WisardResult<()>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let addrs = self.addresses(sample)?;
+
+        for (ram, addr) in self.rams.iter_mut().zip(addrs) {
+            let (count, sum) = ram.entry(addr).or_default();
+            *count += 1;
+            *sum += target;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the estimate of every RAM whose addressed position was
+    /// trained, for a given input sample.
+    pub fn estimates<L, T, O>(&self, sample: &Sample<L, T, O>) -> Vec<f64>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_estimates(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the estimate of every RAM whose addressed position was
+    /// trained, for a given input sample, or an error if the sample size
+    /// does not match the input size.
+    pub fn try_estimates<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<f64>>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let addrs = self.addresses(sample)?;
+        Ok(self
+            .rams
+            .iter()
+            .zip(addrs)
+            .filter_map(|(ram, addr)| ram.get(&addr))
+            .map(|&(count, sum)| sum / count as f64)
+            .collect())
+    }
+
+    /// Returns the model prediction for a given input sample.
+    pub fn predict<L, T, O>(&self, sample: &Sample<L, T, O>) -> f64
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample size does not match the input size. The prediction is zero
+    /// if none of the addressed positions was trained.
+    pub fn try_predict<L, T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<f64>
+    where
+        L: Label,
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        let mut estimates = self.try_estimates(sample)?;
+
+        match estimates.is_empty() {
+            true => Ok(0.0),
+            false => Ok(self.aggregation.aggregate(&mut estimates)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use super::*;
+
+    fn sample(bits: BitVec) -> Sample<()> {
+        Sample::from_raw_parts(bits, 1, ())
+    }
+
+    fn model() -> RegressionWisard {
+        let mapping = TupleMapping::contiguous(6, 2);
+        RegressionWisard::from_mapping(mapping)
+    }
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-9, "{left} != {right}");
+    }
+
+    #[test]
+    fn regression_wisard_mean() {
+        let mut model = model();
+        model.fit(&sample(bitvec![1, 1, 0, 0, 0, 0]), 2.0);
+        model.fit(&sample(bitvec![1, 1, 0, 0, 0, 0]), 4.0);
+        model.fit(&sample(bitvec![0, 0, 0, 0, 1, 1]), 9.0);
+
+        let trained = sample(bitvec![1, 1, 0, 0, 0, 0]);
+        assert_close(model.predict(&trained), 11.0 / 3.0);
+        assert_close(model.predict(&sample(bitvec![1, 1, 1, 1, 1, 1])), 6.0);
+        assert_close(model.predict(&sample(bitvec![0, 1, 1, 0, 1, 0])), 0.0);
+
+        let mut estimates = model.estimates(&sample(bitvec![0, 0, 0, 0, 1, 1]));
+        estimates.sort_unstable_by(f64::total_cmp);
+        assert_eq!(estimates, vec![5.0, 9.0, 9.0]);
+    }
+
+    #[test]
+    fn regression_wisard_aggregations() {
+        let mut model = model();
+        model.fit(&sample(bitvec![1, 1, 0, 0, 0, 0]), 1.0);
+        model.fit(&sample(bitvec![0, 0, 1, 1, 0, 0]), 2.0);
+        model.fit(&sample(bitvec![0, 0, 0, 0, 1, 1]), 8.0);
+        let input = sample(bitvec![1, 1, 1, 1, 1, 1]);
+
+        model.set_aggregation(Aggregation::Median);
+        assert_close(model.predict(&input), 2.0);
+        model.set_aggregation(Aggregation::PowerMean(0.0));
+        assert_close(model.predict(&input), 16f64.powf(1.0 / 3.0));
+        model.set_aggregation(Aggregation::PowerMean(2.0));
+        assert_close(model.predict(&input), 23f64.sqrt());
+
+        let model = model.with_aggregation(Aggregation::Mean);
+        assert_close(model.predict(&input), 11.0 / 3.0);
+    }
+
+    #[test]
+    fn regression_wisard_errors() {
+        let mut model = model();
+        assert!(matches!(
+            model.try_fit(&sample(bitvec![1, 1]), 1.0),
+            Err(WisardError::InputSizeMismatch { .. }),
+        ));
+        assert!(model.try_predict(&sample(bitvec![1; 8])).is_err());
+    }
+}
@@ -1,19 +1,36 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    io::{Read, Write},
+    sync::Arc,
+};
 
 use bitvec::prelude::*;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
 use crate::{
-    encode::{Permute, SampleEncoder},
-    filter::{BuildFilter, Filter, PackedLUTFilter, PackedLUTFilterBuilder},
-    model::Discriminator,
+    error::{WisardError, WisardResult},
+    filter::{
+        BuildFilter, CountingFilter, DecrementFilter, Filter, MergeFilter,
+        PackedLUTFilter, PackedLUTFilterBuilder,
+    },
+    model::{
+        format::{read_model, write_model},
+        Bleaching, Discriminator, Explanation, FormatError, FormatResult,
+        MentalImage, ModelHeader, ModelKind, Ranking, TupleMapping,
+    },
     sample::{Label, Sample},
+    util::map_batch,
 };
 
 /// A wrapper around [`WisardBase`](./struct.WisardBase.html) for a traditional
 /// WiSARD model, using boolean values to store its internal state.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BinaryWisard<L: Label> {
+    #[serde(bound = "")]
     base: WisardBase<L, PackedLUTFilter>,
     seed: [u8; 32],
 }
@@ -46,11 +63,23 @@ impl<L: Label> BinaryWisard<L> {
         labels: HashSet<L>,
         seed: [u8; 32],
     ) -> Self {
+        Self::try_with_seed(input_size, addr_size, labels, seed)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`BinaryWisard`](./struct.BinaryWisard.html) instance
+    /// using a given permutation seed, or returns an error if `addr_size` is
+    /// out of range.
+    pub fn try_with_seed(
+        input_size: usize,
+        addr_size: usize,
+        labels: HashSet<L>,
+        seed: [u8; 32],
+    ) -> WisardResult<Self> {
+        let mapping = TupleMapping::try_random(input_size, addr_size, seed)?;
         let builder = PackedLUTFilterBuilder::new(addr_size, 1, 0);
-        let base = WisardBase::from_filter_builder(
-            input_size, addr_size, labels, &builder,
-        );
-        Self { base, seed }
+        let base = WisardBase::from_mapping(mapping, labels, &builder);
+        Ok(Self { base, seed })
     }
 
     /// Returns the internal random seed for the model.
@@ -58,73 +87,357 @@ impl<L: Label> BinaryWisard<L> {
         self.seed
     }
 
+    /// Writes the model using the binary model file format.
+    ///
+    /// See [`ModelHeader`](./struct.ModelHeader.html) for details.
+    pub fn write_to<W: Write>(&self, writer: W) -> FormatResult<()> {
+        let header = ModelHeader::new(
+            ModelKind::Binary,
+            self.base.input_size(),
+            self.base.addr_size(),
+            1,
+            0,
+            Bleaching::default(),
+            self.seed,
+        );
+        write_model(writer, &header, &self.base)
+    }
+
+    /// Reads a model written with the binary model file format.
+    ///
+    /// See [`ModelHeader`](./struct.ModelHeader.html) for details.
+    pub fn read_from<R: Read>(reader: R) -> FormatResult<Self> {
+        let (header, base) = read_model(reader)?;
+
+        if header.kind() != ModelKind::Binary {
+            return Err(FormatError::UnexpectedKind {
+                expected: ModelKind::Binary,
+                found: header.kind(),
+            });
+        }
+
+        Ok(Self {
+            base,
+            seed: header.seed(),
+        })
+    }
+
     /// Fits (trains) the model with a given input sample.
-    pub fn fit(&mut self, sample: &Sample<L>) {
-        let encoder = <Permute>::with_seed(self.seed);
-        let sample = encoder.encode(sample.clone());
-        self.base.fit(&sample)
+    pub fn fit<T, O>(&mut self, sample: &Sample<L, T, O>)
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a given input sample, returning an error
+    /// if the sample is invalid or its label is unknown.
+    pub fn try_fit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_fit(sample)
+    }
+
+    /// Merges another [`BinaryWisard`](./struct.BinaryWisard.html) into the
+    /// model, as if it was also fitted with every sample used to fit `other`.
+    ///
+    /// Both models must have been created with the same parameters and seed,
+    /// such as models trained on different shards of a dataset.
+    pub fn merge(&mut self, other: &Self) {
+        self.try_merge(other).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Merges another [`BinaryWisard`](./struct.BinaryWisard.html) into the
+    /// model, returning an error if their parameters do not match.
+    pub fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        self.base.try_merge(&other.base)
+    }
+
+    /// Returns the mental image of the discriminator of each label.
+    ///
+    /// See [`MentalImage`](./struct.MentalImage.html) for details.
+    pub fn mental_images(&self) -> HashMap<L, MentalImage> {
+        self.base.mental_images()
+    }
+
+    /// Returns the explanation of the model prediction for a given input
+    /// sample.
+    ///
+    /// See [`Explanation`](./struct.Explanation.html) for details.
+    pub fn explain<T, O>(&self, sample: &Sample<L, T, O>) -> Explanation<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.base.explain(sample)
     }
 
     /// Returns the model scores for a given input sample.
-    pub fn scores(&self, sample: &Sample<L>) -> Vec<(usize, L)> {
-        let encoder = <Permute>::with_seed(self.seed);
-        let sample = encoder.encode(sample.clone());
-        self.base.scores(&sample)
+    pub fn scores<T, O>(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_scores(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model scores for a given input sample, or an error if the
+    /// sample is invalid.
+    pub fn try_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_scores(sample)
     }
 
     /// Returns the model prediction for a given input sample.
-    pub fn predict(&self, sample: &Sample<L>) -> L {
-        let encoder = <Permute>::with_seed(self.seed);
-        let sample = encoder.encode(sample.clone());
-        self.base.predict(&sample)
+    pub fn predict<T, O>(&self, sample: &Sample<L, T, O>) -> L
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample is invalid or the model has no labels.
+    pub fn try_predict<T, O>(&self, sample: &Sample<L, T, O>) -> WisardResult<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_predict(sample)
+    }
+
+    /// Returns the sorted model scores for a given input sample.
+    ///
+    /// See [`Ranking`](./struct.Ranking.html) for details.
+    pub fn ranking<T, O>(&self, sample: &Sample<L, T, O>) -> Ranking<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_ranking(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the sorted model scores for a given input sample, or an error
+    /// if the sample is invalid.
+    pub fn try_ranking<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Ranking<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_ranking(sample)
     }
 }
 
-/// The base for a WiSARD model that only includes the discriminators.
-#[derive(Clone, Debug)]
-pub struct WisardBase<L, F>
-where
-    L: Label,
-    F: Filter,
-{
-    disc: HashMap<L, Discriminator<F>>,
+impl<L: Label + Send + Sync> BinaryWisard<L> {
+    /// Fits (trains) the model with a batch of input samples, in parallel
+    /// when the `rayon` feature is enabled.
+    pub fn fit_batch<T, O>(&mut self, samples: &[Sample<L, T, O>])
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base.fit_batch(samples)
+    }
+
+    /// Fits (trains) the model with a batch of input samples, returning an
+    /// error if any sample is invalid or has an unknown label.
+    pub fn try_fit_batch<T, O>(
+        &mut self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base.try_fit_batch(samples)
+    }
+
+    /// Returns the model predictions for a batch of input samples, computed
+    /// in parallel when the `rayon` feature is enabled.
+    pub fn predict_batch<T, O>(&self, samples: &[Sample<L, T, O>]) -> Vec<L>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.try_predict_batch(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model predictions for a batch of input samples, or an
+    /// error if any sample is invalid or the model has no labels.
+    pub fn try_predict_batch<T, O>(
+        &self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<Vec<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base.try_predict_batch(samples)
+    }
 }
 
-impl<L, F> WisardBase<L, F>
-where
-    L: Label,
-    F: Filter,
-{
-    /// Creates a new [`WisardBase`](./struct.WisardBase.html) instance.
+/// A wrapper around [`WisardBase`](./struct.WisardBase.html) for a counting
+/// WiSARD model, using bleaching to untie the discriminator scores.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CountingWisard<L: Label> {
+    #[serde(bound = "")]
+    base: WisardBase<L, PackedLUTFilter>,
+    count_size: usize,
+    seed: [u8; 32],
+    bleaching: Bleaching,
+}
+
+impl<L: Label> CountingWisard<L> {
+    /// Creates a new [`CountingWisard`](./struct.CountingWisard.html) instance
+    /// using `rand::random()` as the permutation seed.
     ///
     /// The `input_size` value determines the total number of input bits.
     /// The `addr_size` value corresponds to the address size of the RAMs.
+    /// The `count_size` value determines the number of bits of each counter.
     /// The `labels` set must contain all the expected sample labels.
-    /// The `builder` value must be an instance of a type which implements
-    /// the [`FilterBuilder`](./trait.FilterBuilder.html) trait, using the same
-    /// `addr_size` as provided before and serving as a backend for the RAMs.
-    pub fn from_filter_builder<B>(
+    pub fn new(
         input_size: usize,
         addr_size: usize,
+        count_size: usize,
         labels: HashSet<L>,
-        builder: &B,
-    ) -> Self
-    where
-        B: BuildFilter<Filter = F>,
-    {
-        Self {
-            disc: labels
-                .into_iter()
-                .map(|label| {
-                    (
-                        label,
-                        Discriminator::from_filter_builder(
-                            input_size, addr_size, builder,
-                        ),
-                    )
-                })
-                .collect(),
+    ) -> Self {
+        Self::with_seed(
+            input_size,
+            addr_size,
+            count_size,
+            labels,
+            rand::random(),
+        )
+    }
+
+    /// Creates a new [`CountingWisard`](./struct.CountingWisard.html) instance
+    /// using a given permutation seed.
+    ///
+    /// The `input_size` value determines the total number of input bits.
+    /// The `addr_size` value corresponds to the address size of the RAMs.
+    /// The `count_size` value determines the number of bits of each counter.
+    /// The `labels` set must contain all the expected sample labels.
+    /// The `seed` value determines the permutation seed.
+    pub fn with_seed(
+        input_size: usize,
+        addr_size: usize,
+        count_size: usize,
+        labels: HashSet<L>,
+        seed: [u8; 32],
+    ) -> Self {
+        Self::try_with_seed(input_size, addr_size, count_size, labels, seed)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a new [`CountingWisard`](./struct.CountingWisard.html) instance
+    /// using a given permutation seed, or returns an error if `addr_size` is
+    /// out of range.
+    pub fn try_with_seed(
+        input_size: usize,
+        addr_size: usize,
+        count_size: usize,
+        labels: HashSet<L>,
+        seed: [u8; 32],
+    ) -> WisardResult<Self> {
+        let mapping = TupleMapping::try_random(input_size, addr_size, seed)?;
+        let builder = PackedLUTFilterBuilder::new(addr_size, count_size, 0);
+        let base = WisardBase::from_mapping(mapping, labels, &builder);
+        Ok(Self {
+            base,
+            count_size,
+            seed,
+            bleaching: Bleaching::default(),
+        })
+    }
+
+    /// Returns the internal random seed for the model.
+    pub fn seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Returns the number of bits of each RAM counter.
+    pub fn count_size(&self) -> usize {
+        self.count_size
+    }
+
+    /// Returns the bleaching strategy used for predictions.
+    pub fn bleaching(&self) -> Bleaching {
+        self.bleaching
+    }
+
+    /// Sets the bleaching strategy used for predictions.
+    pub fn set_bleaching(&mut self, bleaching: Bleaching) {
+        self.bleaching = bleaching;
+    }
+
+    /// Writes the model using the binary model file format.
+    ///
+    /// See [`ModelHeader`](./struct.ModelHeader.html) for details.
+    pub fn write_to<W: Write>(&self, writer: W) -> FormatResult<()> {
+        let header = ModelHeader::new(
+            ModelKind::Counting,
+            self.base.input_size(),
+            self.base.addr_size(),
+            self.count_size,
+            0,
+            self.bleaching,
+            self.seed,
+        );
+        write_model(writer, &header, &self.base)
+    }
+
+    /// Reads a model written with the binary model file format.
+    ///
+    /// See [`ModelHeader`](./struct.ModelHeader.html) for details.
+    pub fn read_from<R: Read>(reader: R) -> FormatResult<Self> {
+        let (header, base) = read_model(reader)?;
+
+        if header.kind() != ModelKind::Counting {
+            return Err(FormatError::UnexpectedKind {
+                expected: ModelKind::Counting,
+                found: header.kind(),
+            });
         }
+
+        Ok(Self {
+            base,
+            count_size: header.count_size(),
+            seed: header.seed(),
+            bleaching: header.bleaching(),
+        })
     }
 
     /// Fits (trains) the model with a given input sample.
@@ -134,80 +447,1287 @@ where
         T::Mem: Serialize,
         O: BitOrder + Clone,
     {
-        self.disc.get_mut(sample.label()).unwrap().fit(sample)
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
     }
 
-    /// Returns the model scores for a given input sample.
-    pub fn scores<T, O>(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)>
+    /// Fits (trains) the model with a given input sample, returning an error
+    /// if the sample is invalid or its label is unknown.
+    pub fn try_fit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
     where
         T: BitStore + Clone + DeserializeOwned,
         T::Mem: Serialize,
         O: BitOrder + Clone,
     {
-        self.disc
-            .keys()
-            .map(|label| (self.disc[label].score(sample), *label))
-            .collect()
+        self.base.try_fit(sample)
     }
 
-    /// Returns the model prediction for a given input sample.
-    pub fn predict<T, O>(&self, sample: &Sample<L, T, O>) -> L
+    /// Unfits (untrains) the model with a given input sample, reverting a
+    /// previous call to [`fit`](#method.fit) with it.
+    pub fn unfit<T, O>(&mut self, sample: &Sample<L, T, O>)
     where
         T: BitStore + Clone + DeserializeOwned,
         T::Mem: Serialize,
         O: BitOrder + Clone,
     {
-        self.scores(sample)
-            .into_iter()
-            .max_by(|a, b| a.0.cmp(&b.0))
-            .unwrap()
-            .1
+        self.try_unfit(sample).unwrap_or_else(|err| panic!("{err}"))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use bitvec::prelude::*;
-    use serde::Deserialize;
+    /// Unfits (untrains) the model with a given input sample, returning an
+    /// error if the sample is invalid or its label is unknown.
+    pub fn try_unfit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_unfit(sample)
+    }
 
-    use crate::sample::Sample;
+    /// Merges another [`CountingWisard`](./struct.CountingWisard.html) into the
+    /// model, as if it was also fitted with every sample used to fit `other`.
+    ///
+    /// Both models must have been created with the same parameters and seed,
+    /// such as models trained on different shards of a dataset.
+    pub fn merge(&mut self, other: &Self) {
+        self.try_merge(other).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-    use super::*;
+    /// Merges another [`CountingWisard`](./struct.CountingWisard.html) into the
+    /// model, returning an error if their parameters do not match.
+    pub fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        self.base.try_merge(&other.base)
+    }
 
-    #[test]
-    fn binary_wisard_hot_cold() {
-        #[derive(
-            Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize,
-        )]
-        enum Label {
-            Cold,
-            Hot,
-        }
+    /// Returns the mental image of the discriminator of each label.
+    ///
+    /// See [`MentalImage`](./struct.MentalImage.html) for details.
+    pub fn mental_images(&self) -> HashMap<L, MentalImage> {
+        self.base.mental_images()
+    }
 
-        let input_size = 8;
-        let addr_size = 2;
-        let labels = HashSet::from_iter([Label::Cold, Label::Hot].into_iter());
-        let mut model = BinaryWisard::new(input_size, addr_size, labels);
+    /// Returns the bleached model scores for a given input sample.
+    pub fn scores<T, O>(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_scores(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let samples = vec![
-            (bitvec![1, 1, 1, 0, 0, 0, 0, 0], Label::Cold),
-            (bitvec![1, 1, 1, 1, 0, 0, 0, 0], Label::Cold),
-            (bitvec![0, 0, 0, 0, 1, 1, 1, 1], Label::Hot),
-            (bitvec![0, 0, 0, 0, 0, 1, 1, 1], Label::Hot),
-        ];
+    /// Returns the bleached model scores for a given input sample, or an
+    /// error if the sample is invalid.
+    pub fn try_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_bleached_scores(sample, self.bleaching)
+    }
 
-        let encoded_samples = samples
-            .into_iter()
-            .map(|(v, l)| Sample::from_raw_parts(v, addr_size, l))
-            .collect::<Vec<_>>();
+    /// Returns the model prediction for a given input sample.
+    pub fn predict<T, O>(&self, sample: &Sample<L, T, O>) -> L
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        for sample in encoded_samples.iter() {
-            model.fit(sample);
-        }
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample is invalid or the model has no labels.
+    pub fn try_predict<T, O>(&self, sample: &Sample<L, T, O>) -> WisardResult<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.base.try_predict_bleaching(sample, self.bleaching)
+    }
 
-        for sample in encoded_samples.iter() {
-            let pred = model.predict(sample);
-            assert_eq!(&pred, sample.label());
-        }
+    /// Returns the explanation of the model prediction for a given input
+    /// sample, where a RAM fires if its counter is above the bleaching
+    /// threshold used for the prediction.
+    ///
+    /// See [`Explanation`](./struct.Explanation.html) for details.
+    pub fn explain<T, O>(&self, sample: &Sample<L, T, O>) -> Explanation<L>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        self.base.explain_bleaching(sample, self.bleaching)
+    }
+
+    /// Returns the sorted bleached model scores for a given input sample.
+    ///
+    /// See [`Ranking`](./struct.Ranking.html) for details.
+    pub fn ranking<T, O>(&self, sample: &Sample<L, T, O>) -> Ranking<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_ranking(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the sorted bleached model scores for a given input sample, or
+    /// an error if the sample is invalid.
+    pub fn try_ranking<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Ranking<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        let rams = self.base.mapping().len();
+        Ok(Ranking::new(self.try_scores(sample)?, rams))
+    }
+}
+
+impl<L: Label + Send + Sync> CountingWisard<L> {
+    /// Fits (trains) the model with a batch of input samples, in parallel
+    /// when the `rayon` feature is enabled.
+    pub fn fit_batch<T, O>(&mut self, samples: &[Sample<L, T, O>])
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base.fit_batch(samples)
+    }
+
+    /// Fits (trains) the model with a batch of input samples, returning an
+    /// error if any sample is invalid or has an unknown label.
+    pub fn try_fit_batch<T, O>(
+        &mut self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base.try_fit_batch(samples)
+    }
+
+    /// Returns the model predictions for a batch of input samples, computed
+    /// in parallel when the `rayon` feature is enabled.
+    pub fn predict_batch<T, O>(&self, samples: &[Sample<L, T, O>]) -> Vec<L>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.try_predict_batch(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model predictions for a batch of input samples, or an
+    /// error if any sample is invalid or the model has no labels.
+    pub fn try_predict_batch<T, O>(
+        &self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<Vec<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.base
+            .try_predict_bleaching_batch(samples, self.bleaching)
+    }
+}
+
+/// The base for a WiSARD model that only includes the discriminators.
+///
+/// The tuple mapping is shared by all the discriminators, and it is stored
+/// only once when the model is serialized.
+#[derive(Clone, Debug)]
+pub struct WisardBase<L, F>
+where
+    L: Label,
+    F: Filter,
+{
+    mapping: Arc<TupleMapping>,
+    disc: HashMap<L, Discriminator<F>>,
+}
+
+/// The serialized form of a model whose discriminators share a mapping.
+#[derive(Serialize, Deserialize)]
+struct SharedMappingRepr<M, D> {
+    mapping: M,
+    filters: D,
+}
+
+impl<L, F> Serialize for WisardBase<L, F>
+where
+    L: Label,
+    F: Filter + Serialize,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let filters = self
+            .disc
+            .iter()
+            .map(|(label, disc)| (label, disc.filters()))
+            .collect::<HashMap<_, _>>();
+        let repr = SharedMappingRepr {
+            mapping: self.mapping.as_ref(),
+            filters,
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, L, F> Deserialize<'de> for WisardBase<L, F>
+where
+    L: Label,
+    F: Filter + DeserializeOwned,
+{
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let repr =
+            SharedMappingRepr::<TupleMapping, HashMap<L, Vec<F>>>::deserialize(
+                deserializer,
+            )?;
+        let mapping = Arc::new(repr.mapping);
+        let disc = repr
+            .filters
+            .into_iter()
+            .map(|(label, filters)| {
+                if filters.len() != mapping.len() {
+                    return Err(de::Error::custom(
+                        "discriminator size does not match the mapping",
+                    ));
+                }
+
+                let disc =
+                    Discriminator::from_filters(mapping.clone(), filters);
+                Ok((label, disc))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { mapping, disc })
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: Filter,
+{
+    /// Creates a new [`WisardBase`](./struct.WisardBase.html) instance.
+    ///
+    /// The `input_size` value determines the total number of input bits.
+    /// The `addr_size` value corresponds to the address size of the RAMs.
+    /// The `labels` set must contain all the expected sample labels.
+    /// The `builder` value must be an instance of a type which implements
+    /// the [`FilterBuilder`](./trait.FilterBuilder.html) trait, using the same
+    /// `addr_size` as provided before and serving as a backend for the RAMs.
+    pub fn from_filter_builder<B>(
+        input_size: usize,
+        addr_size: usize,
+        labels: HashSet<L>,
+        builder: &B,
+    ) -> Self
+    where
+        B: BuildFilter<Filter = F>,
+    {
+        let mapping = TupleMapping::contiguous(input_size, addr_size);
+        Self::from_mapping(mapping, labels, builder)
+    }
+
+    /// Creates a new [`WisardBase`](./struct.WisardBase.html) instance using
+    /// a given [`TupleMapping`](./struct.TupleMapping.html), which is shared
+    /// by all the discriminators.
+    ///
+    /// The `mapping` value determines which input bits feed each RAM.
+    /// The `labels` set must contain all the expected sample labels.
+    /// The `builder` value must be an instance of a type which implements
+    /// the [`FilterBuilder`](./trait.FilterBuilder.html) trait, using the
+    /// address size of the `mapping` and serving as a backend for the RAMs.
+    pub fn from_mapping<B>(
+        mapping: TupleMapping,
+        labels: HashSet<L>,
+        builder: &B,
+    ) -> Self
+    where
+        B: BuildFilter<Filter = F>,
+    {
+        let mapping = Arc::new(mapping);
+        let disc = labels
+            .into_iter()
+            .map(|label| {
+                let disc = Discriminator::from_shared_mapping(
+                    mapping.clone(),
+                    builder,
+                );
+                (label, disc)
+            })
+            .collect();
+        Self { mapping, disc }
+    }
+
+    /// Creates a [`WisardBase`](./struct.WisardBase.html) instance from an
+    /// already built set of discriminators, which must use `mapping`.
+    pub(crate) fn from_discriminators(
+        mapping: Arc<TupleMapping>,
+        disc: HashMap<L, Discriminator<F>>,
+    ) -> Self {
+        Self { mapping, disc }
+    }
+
+    /// Returns the model input size.
+    pub fn input_size(&self) -> usize {
+        self.mapping.input_size()
+    }
+
+    /// Returns the model address size.
+    pub fn addr_size(&self) -> usize {
+        self.mapping.addr_size()
+    }
+
+    /// Returns the tuple mapping shared by all the discriminators.
+    pub fn mapping(&self) -> &TupleMapping {
+        &self.mapping
+    }
+
+    /// Returns an iterator over the model labels.
+    pub fn labels(&self) -> impl Iterator<Item = &L> {
+        self.disc.keys()
+    }
+
+    /// Returns an iterator over the labeled discriminators of the model.
+    pub(crate) fn discriminators(
+        &self,
+    ) -> impl Iterator<Item = (&L, &Discriminator<F>)> {
+        self.disc.iter()
+    }
+
+    /// Returns the RAM addresses of a given input sample, which are shared
+    /// by all the discriminators, or an error if the sample size does not
+    /// match the input size.
+    fn addresses<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<usize>>
+    where
+        T: BitStore + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder,
+    {
+        if sample.len() != self.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: self.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        Ok(self.mapping.addresses(sample.raw_bits()).collect())
+    }
+
+    /// Fits (trains) the model with a given input sample.
+    pub fn fit<T, O>(&mut self, sample: &Sample<L, T, O>)
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_fit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a given input sample, returning an error
+    /// if the sample is invalid or its label is unknown.
+    pub fn try_fit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        let disc = self.disc.get_mut(sample.label()).ok_or_else(|| {
+            WisardError::UnknownLabel(format!("{:?}", sample.label()))
+        })?;
+
+        if sample.len() != disc.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: disc.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        let addrs = self.mapping.addresses(sample.raw_bits());
+        disc.fit_addresses(addrs)
+    }
+
+    /// Returns the model scores for a given input sample.
+    pub fn scores<T, O>(&self, sample: &Sample<L, T, O>) -> Vec<(usize, L)>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_scores(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model scores for a given input sample, or an error if the
+    /// sample is invalid.
+    pub fn try_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<Vec<(usize, L)>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        let addrs = self.addresses(sample)?;
+        self.disc
+            .iter()
+            .map(|(label, disc)| {
+                Ok((disc.score_addresses(addrs.iter().copied())?, *label))
+            })
+            .collect()
+    }
+
+    /// Returns the model prediction for a given input sample.
+    pub fn predict<T, O>(&self, sample: &Sample<L, T, O>) -> L
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_predict(sample)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample, or an error if
+    /// the sample is invalid or the model has no labels.
+    pub fn try_predict<T, O>(&self, sample: &Sample<L, T, O>) -> WisardResult<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        best_label(self.try_scores(sample)?)
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label + Send + Sync,
+    F: Filter + Send + Sync,
+{
+    /// Fits (trains) the model with a batch of input samples.
+    ///
+    /// The RAM addresses of the samples are computed in parallel and every
+    /// discriminator is trained in parallel when the `rayon` feature is
+    /// enabled. The resulting model is identical to the one obtained by
+    /// fitting the samples sequentially.
+    pub fn fit_batch<T, O>(&mut self, samples: &[Sample<L, T, O>])
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.try_fit_batch(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fits (trains) the model with a batch of input samples, returning an
+    /// error if any sample is invalid or has an unknown label. The model is
+    /// left unchanged in case of errors.
+    pub fn try_fit_batch<T, O>(
+        &mut self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        let addrs = map_batch(samples, |sample| {
+            if !self.disc.contains_key(sample.label()) {
+                let label = format!("{:?}", sample.label());
+                return Err(WisardError::UnknownLabel(label));
+            }
+
+            self.addresses(sample)
+        })?;
+
+        let mut groups = HashMap::<L, Vec<&[usize]>>::new();
+
+        for (sample, addrs) in samples.iter().zip(addrs.iter()) {
+            groups.entry(*sample.label()).or_default().push(addrs);
+        }
+
+        let train = |(label, disc): (&L, &mut Discriminator<F>)| {
+            for addrs in groups.get(label).into_iter().flatten() {
+                disc.fit_addresses(addrs.iter().copied())?;
+            }
+
+            Ok(())
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            self.disc.par_iter_mut().try_for_each(train)
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            self.disc.iter_mut().try_for_each(train)
+        }
+    }
+
+    /// Returns the model predictions for a batch of input samples, which are
+    /// computed in parallel when the `rayon` feature is enabled.
+    pub fn predict_batch<T, O>(&self, samples: &[Sample<L, T, O>]) -> Vec<L>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.try_predict_batch(samples)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model predictions for a batch of input samples, or an
+    /// error if any sample is invalid or the model has no labels.
+    pub fn try_predict_batch<T, O>(
+        &self,
+        samples: &[Sample<L, T, O>],
+    ) -> WisardResult<Vec<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        map_batch(samples, |sample| self.try_predict(sample))
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: CountingFilter,
+{
+    /// Returns the model scores for a given input sample after untying them
+    /// with a given [`Bleaching`](./enum.Bleaching.html) strategy.
+    pub fn bleached_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> Vec<(usize, L)>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_bleached_scores(sample, bleaching)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model scores for a given input sample after untying them
+    /// with a given [`Bleaching`](./enum.Bleaching.html) strategy, or an
+    /// error if the sample is invalid.
+    pub fn try_bleached_scores<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> WisardResult<Vec<(usize, L)>>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        let addrs = self.addresses(sample)?;
+        let mut labels = Vec::with_capacity(self.disc.len());
+        let mut counters = Vec::with_capacity(self.disc.len());
+
+        for (label, disc) in self.disc.iter() {
+            labels.push(*label);
+            counters.push(disc.counters_addresses(addrs.iter().copied())?);
+        }
+
+        Ok(bleaching
+            .scores(&counters)
+            .into_iter()
+            .zip(labels)
+            .collect())
+    }
+
+    /// Returns the model prediction for a given input sample using a given
+    /// [`Bleaching`](./enum.Bleaching.html) strategy.
+    pub fn predict_bleaching<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> L
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_predict_bleaching(sample, bleaching)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model prediction for a given input sample using a given
+    /// [`Bleaching`](./enum.Bleaching.html) strategy, or an error if the
+    /// sample is invalid or the model has no labels.
+    pub fn try_predict_bleaching<T, O>(
+        &self,
+        sample: &Sample<L, T, O>,
+        bleaching: Bleaching,
+    ) -> WisardResult<L>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        best_label(self.try_bleached_scores(sample, bleaching)?)
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: DecrementFilter,
+{
+    /// Unfits (untrains) the model with a given input sample, reverting a
+    /// previous call to [`fit`](#method.fit) with it.
+    ///
+    /// This can be used to forget a sample or to correct its label, by
+    /// unfitting it and then fitting it again with the right label.
+    pub fn unfit<T, O>(&mut self, sample: &Sample<L, T, O>)
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        self.try_unfit(sample).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Unfits (untrains) the model with a given input sample, returning an
+    /// error if the sample is invalid or its label is unknown.
+    pub fn try_unfit<T, O>(
+        &mut self,
+        sample: &Sample<L, T, O>,
+    ) -> WisardResult<()>
+    where
+        T: BitStore + Clone + DeserializeOwned,
+        T::Mem: Serialize,
+        O: BitOrder + Clone,
+    {
+        let disc = self.disc.get_mut(sample.label()).ok_or_else(|| {
+            WisardError::UnknownLabel(format!("{:?}", sample.label()))
+        })?;
+
+        if sample.len() != disc.input_size() {
+            return Err(WisardError::InputSizeMismatch {
+                expected: disc.input_size(),
+                found: sample.len(),
+            });
+        }
+
+        let addrs = self.mapping.addresses(sample.raw_bits());
+        disc.unfit_addresses(addrs)
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label,
+    F: MergeFilter,
+{
+    /// Merges the discriminators of `other` into the model, as if it was also
+    /// fitted with every sample used to fit `other`.
+    ///
+    /// This allows training models on different shards of a dataset, possibly
+    /// on different machines, and combining them afterwards.
+    pub fn merge(&mut self, other: &Self) {
+        self.try_merge(other).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Merges the discriminators of `other` into the model, returning an
+    /// error if their mappings, labels or filter parameters do not match.
+    /// The model is left unchanged in case of errors.
+    pub fn try_merge(&mut self, other: &Self) -> WisardResult<()> {
+        if self.mapping != other.mapping {
+            return Err(WisardError::IncompatibleMerge(
+                "models have different mappings",
+            ));
+        }
+
+        if self.disc.len() != other.disc.len() {
+            return Err(WisardError::IncompatibleMerge(
+                "models have different labels",
+            ));
+        }
+
+        for (label, disc) in self.disc.iter() {
+            let other = other.disc.get(label).ok_or(
+                WisardError::IncompatibleMerge("models have different labels"),
+            )?;
+            disc.check_merge(other)?;
+        }
+
+        for (label, disc) in self.disc.iter_mut() {
+            disc.try_merge(&other.disc[label])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<L, F> WisardBase<L, F>
+where
+    L: Label + Send + Sync,
+    F: CountingFilter + Send + Sync,
+{
+    /// Returns the model predictions for a batch of input samples using a
+    /// given [`Bleaching`](./enum.Bleaching.html) strategy, which are
+    /// computed in parallel when the `rayon` feature is enabled.
+    pub fn predict_bleaching_batch<T, O>(
+        &self,
+        samples: &[Sample<L, T, O>],
+        bleaching: Bleaching,
+    ) -> Vec<L>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        self.try_predict_bleaching_batch(samples, bleaching)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the model predictions for a batch of input samples using a
+    /// given [`Bleaching`](./enum.Bleaching.html) strategy, or an error if
+    /// any sample is invalid or the model has no labels.
+    pub fn try_predict_bleaching_batch<T, O>(
+        &self,
+        samples: &[Sample<L, T, O>],
+        bleaching: Bleaching,
+    ) -> WisardResult<Vec<L>>
+    where
+        T: BitStore + Clone + DeserializeOwned + Sync,
+        T::Mem: Serialize,
+        O: BitOrder + Clone + Sync,
+    {
+        map_batch(samples, |sample| {
+            self.try_predict_bleaching(sample, bleaching)
+        })
+    }
+}
+
+/// Returns the label with the highest score, breaking ties in favor of the
+/// smallest label.
+pub(crate) fn best_label<L: Label>(scores: Vec<(usize, L)>) -> WisardResult<L> {
+    scores
+        .into_iter()
+        .min_by(score_order)
+        .map(|(_, label)| label)
+        .ok_or(WisardError::NoLabels)
+}
+
+/// Sorts `items` from the highest score to the lowest, so that the first
+/// item holds the label returned by [`best_label`] for the same scores.
+pub(crate) fn sort_by_score<T, L, F>(items: &mut [T], key: F)
+where
+    L: Label,
+    F: Fn(&T) -> (usize, L),
+{
+    items.sort_by(|a, b| score_order(&key(a), &key(b)));
+}
+
+/// Orders scores from the highest to the lowest, and equal scores from the
+/// smallest label to the largest, so that ties do not depend on the
+/// iteration order of the discriminators.
+fn score_order<L: Label>(a: &(usize, L), b: &(usize, L)) -> Ordering {
+    b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use crate::{
+        filter::{LUTFilter, LUTFilterBuilder},
+        model::RegressionWisard,
+        sample::Sample,
+    };
+
+    use super::*;
+
+    #[test]
+    fn binary_wisard_hot_cold() {
+        #[derive(
+            Copy,
+            Clone,
+            Debug,
+            Eq,
+            PartialEq,
+            Ord,
+            PartialOrd,
+            Hash,
+            Serialize,
+            Deserialize,
+        )]
+        enum Label {
+            Cold,
+            Hot,
+        }
+
+        let input_size = 8;
+        let addr_size = 2;
+        let labels = HashSet::from_iter([Label::Cold, Label::Hot]);
+        let mut model = BinaryWisard::new(input_size, addr_size, labels);
+
+        let samples = vec![
+            (bitvec![1, 1, 1, 0, 0, 0, 0, 0], Label::Cold),
+            (bitvec![1, 1, 1, 1, 0, 0, 0, 0], Label::Cold),
+            (bitvec![0, 0, 0, 0, 1, 1, 1, 1], Label::Hot),
+            (bitvec![0, 0, 0, 0, 0, 1, 1, 1], Label::Hot),
+        ];
+
+        let encoded_samples = samples
+            .into_iter()
+            .map(|(v, l)| Sample::from_raw_parts(v, addr_size, l))
+            .collect::<Vec<_>>();
+
+        for sample in encoded_samples.iter() {
+            model.fit(sample);
+        }
+
+        for sample in encoded_samples.iter() {
+            let pred = model.predict(sample);
+            assert_eq!(&pred, sample.label());
+        }
+    }
+
+    #[test]
+    fn counting_wisard_bleaching() {
+        let input_size = 4;
+        let addr_size = 2;
+        let labels = HashSet::from_iter([0usize, 1usize]);
+        let seed = [0u8; 32];
+
+        let samples = vec![
+            (bitvec![1, 1, 1, 1], 0),
+            (bitvec![1, 1, 1, 1], 0),
+            (bitvec![1, 1, 1, 1], 0),
+            (bitvec![1, 1, 1, 1], 1),
+            (bitvec![0, 0, 0, 0], 1),
+        ];
+
+        let samples = samples
+            .into_iter()
+            .map(|(v, l)| Sample::from_raw_parts(v, addr_size, l))
+            .collect::<Vec<_>>();
+
+        for bleaching in [Bleaching::Linear, Bleaching::BinarySearch] {
+            let mut model = CountingWisard::with_seed(
+                input_size,
+                addr_size,
+                4,
+                labels.clone(),
+                seed,
+            );
+            model.set_bleaching(bleaching);
+
+            for sample in samples.iter() {
+                model.fit(sample);
+            }
+
+            let mut scores = model.scores(&samples[0]);
+            scores.sort_by_key(|(_, label)| *label);
+            assert_eq!(scores, vec![(2, 0), (0, 1)]);
+            assert_eq!(model.predict(&samples[0]), 0);
+            assert_eq!(model.predict(&samples[4]), 1);
+        }
+    }
+
+    fn round_trip_samples() -> Vec<Sample<usize>> {
+        vec![
+            (bitvec![1, 1, 1, 0, 0, 0, 0, 0], 0),
+            (bitvec![1, 1, 1, 1, 0, 0, 0, 0], 0),
+            (bitvec![1, 1, 0, 0, 0, 0, 0, 0], 0),
+            (bitvec![0, 0, 0, 0, 1, 1, 1, 1], 1),
+            (bitvec![0, 0, 0, 0, 0, 1, 1, 1], 1),
+            (bitvec![0, 0, 0, 1, 1, 1, 1, 1], 2),
+        ]
+        .into_iter()
+        .map(|(v, l)| Sample::from_raw_parts(v, 1, l))
+        .collect()
+    }
+
+    fn sorted<L: Ord + Copy>(mut scores: Vec<(usize, L)>) -> Vec<(usize, L)> {
+        scores.sort_by_key(|(_, label)| *label);
+        scores
+    }
+
+    #[test]
+    fn wisard_invalid_addr_size() {
+        let labels = HashSet::from_iter([0, 1]);
+        let too_large = usize::BITS as usize + 1;
+        let model = BinaryWisard::try_with_seed(
+            128,
+            too_large,
+            labels.clone(),
+            [0; 32],
+        );
+        assert!(model.is_err());
+        let model = CountingWisard::try_with_seed(128, 0, 2, labels, [0; 32]);
+        assert!(model.is_err());
+        let model = RegressionWisard::try_with_seed(128, 0, [0; 32]);
+        assert!(model.is_err());
+    }
+
+    #[test]
+    fn binary_wisard_serde_round_trip() {
+        let labels = HashSet::from_iter([0usize, 1, 2]);
+        let samples = round_trip_samples();
+        let mut model = BinaryWisard::new(8, 2, labels);
+
+        for sample in samples.iter() {
+            model.fit(sample);
+        }
+
+        let bytes = bincode::serialize(&model).unwrap();
+        let loaded: BinaryWisard<usize> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(model.seed(), loaded.seed());
+
+        for sample in samples.iter() {
+            let expected = sorted(model.scores(sample));
+            assert_eq!(sorted(loaded.scores(sample)), expected);
+        }
+    }
+
+    #[test]
+    fn counting_wisard_serde_round_trip() {
+        let labels = HashSet::from_iter([0usize, 1, 2]);
+        let samples = round_trip_samples();
+        let mut model = CountingWisard::new(8, 2, 3, labels);
+        model.set_bleaching(Bleaching::BinarySearch);
+
+        for sample in samples.iter().chain(samples.iter()) {
+            model.fit(sample);
+        }
+
+        let bytes = bincode::serialize(&model).unwrap();
+        let loaded: CountingWisard<usize> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(model.seed(), loaded.seed());
+        assert_eq!(model.bleaching(), loaded.bleaching());
+
+        for sample in samples.iter() {
+            let expected = sorted(model.scores(sample));
+            assert_eq!(sorted(loaded.scores(sample)), expected);
+        }
+    }
+
+    #[test]
+    fn wisard_base_serde_round_trip() {
+        let labels = HashSet::from_iter([0usize, 1, 2]);
+        let samples = round_trip_samples();
+        let builder = LUTFilterBuilder::new(2, 0u8);
+        let mut model = WisardBase::from_filter_builder(8, 2, labels, &builder);
+
+        for sample in samples.iter() {
+            model.fit(sample);
+        }
+
+        let bytes = bincode::serialize(&model).unwrap();
+        let loaded: WisardBase<usize, LUTFilter> =
+            bincode::deserialize(&bytes).unwrap();
+        assert!(loaded
+            .discriminators()
+            .all(|(_, disc)| std::ptr::eq(disc.mapping(), loaded.mapping())));
+
+        for sample in samples.iter() {
+            let expected = sorted(model.scores(sample));
+            assert_eq!(sorted(loaded.scores(sample)), expected);
+        }
+    }
+
+    #[test]
+    fn wisard_base_errors() {
+        let labels = HashSet::from_iter([0usize, 1]);
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        let mut model = WisardBase::from_filter_builder(4, 2, labels, &builder);
+
+        let unknown = Sample::from_raw_parts(bitvec![0, 1, 1, 0], 1, 2usize);
+        assert!(matches!(
+            model.try_fit(&unknown),
+            Err(WisardError::UnknownLabel(_)),
+        ));
+
+        let short = Sample::from_raw_parts(bitvec![0, 1], 1, 0usize);
+        assert!(matches!(
+            model.try_predict(&short),
+            Err(WisardError::InputSizeMismatch { .. }),
+        ));
+
+        let empty = WisardBase::<usize, PackedLUTFilter>::from_filter_builder(
+            4,
+            2,
+            HashSet::new(),
+            &builder,
+        );
+        let sample = Sample::from_raw_parts(bitvec![0, 1, 1, 0], 1, 0usize);
+        assert!(matches!(
+            empty.try_predict(&sample),
+            Err(WisardError::NoLabels)
+        ));
+    }
+
+    #[test]
+    fn wisard_base_mapping() {
+        let labels = HashSet::from_iter([0usize, 1]);
+        let builder = PackedLUTFilterBuilder::new(2, 1, 0);
+        let tuples = vec![vec![0, 7], vec![1, 6], vec![2, 5], vec![3, 4]];
+        let mapping = TupleMapping::from_indices(8, tuples).unwrap();
+        let mut model = WisardBase::from_mapping(mapping, labels, &builder);
+
+        let samples = vec![
+            (bitvec![1, 1, 0, 0, 0, 0, 1, 1], 0),
+            (bitvec![1, 1, 1, 0, 0, 1, 1, 1], 0),
+            (bitvec![0, 0, 1, 1, 1, 1, 0, 0], 1),
+            (bitvec![0, 0, 0, 1, 1, 0, 0, 0], 1),
+        ]
+        .into_iter()
+        .map(|(v, l)| Sample::from_raw_parts(v, 1, l))
+        .collect::<Vec<_>>();
+
+        for sample in samples.iter() {
+            model.fit(sample);
+        }
+
+        for sample in samples.iter() {
+            assert_eq!(&model.predict(sample), sample.label());
+        }
+
+        let sample =
+            Sample::from_raw_parts(bitvec![1, 1, 0, 0, 0, 0, 1, 1], 1, 0);
+        let mut scores = model.scores(&sample);
+        scores.sort_by_key(|(_, label)| *label);
+        assert_eq!(scores, vec![(4, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn wisard_base_shared_addresses() {
+        let labels = HashSet::from_iter(0usize..8);
+        let builder = PackedLUTFilterBuilder::new(3, 2, 0);
+        let mapping = TupleMapping::overlapping(12, 3, 2);
+        let mut model = WisardBase::from_mapping(mapping, labels, &builder);
+
+        let samples = (0..64usize)
+            .map(|i| {
+                let bits: BitVec =
+                    (0..12).map(|j| (i * 7 + j * 5) % 3 == 0).collect();
+                Sample::from_raw_parts(bits, 1, i % 8)
+            })
+            .collect::<Vec<_>>();
+
+        for sample in samples.iter() {
+            model.fit(sample);
+        }
+
+        for sample in samples.iter() {
+            let scores = model.scores(sample);
+            let bleached = model.bleached_scores(sample, Bleaching::Linear);
+            let counters = model
+                .discriminators()
+                .map(|(_, disc)| disc.counters(sample))
+                .collect::<Vec<_>>();
+            assert_eq!(bleached.len(), scores.len());
+            assert_eq!(
+                bleached.iter().map(|(s, _)| *s).collect::<Vec<_>>(),
+                Bleaching::Linear.scores(&counters),
+            );
+
+            for (score, label) in scores {
+                let disc = model.discriminators().find(|(l, _)| **l == label);
+                assert_eq!(disc.unwrap().1.score(sample), score);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_matches_sequential() {
+        let samples = (0..48usize)
+            .map(|i| {
+                let bits: BitVec =
+                    (0..16).map(|j| (i * 5 + j * 3) % 4 == 0).collect();
+                Sample::from_raw_parts(bits, 1, i % 3)
+            })
+            .collect::<Vec<_>>();
+
+        let labels = HashSet::from_iter(0usize..3);
+        let seed = [5u8; 32];
+        let mut sequential =
+            CountingWisard::with_seed(16, 4, 3, labels.clone(), seed);
+        let mut batch = CountingWisard::with_seed(16, 4, 3, labels, seed);
+
+        for sample in samples.iter() {
+            sequential.fit(sample);
+        }
+
+        batch.fit_batch(&samples);
+
+        for sample in samples.iter() {
+            let mut expected = sequential.scores(sample);
+            let mut found = batch.scores(sample);
+            expected.sort_by_key(|(_, label)| *label);
+            found.sort_by_key(|(_, label)| *label);
+            assert_eq!(expected, found);
+        }
+
+        let expected = samples
+            .iter()
+            .map(|sample| batch.predict(sample))
+            .collect::<Vec<_>>();
+        assert_eq!(batch.predict_batch(&samples), expected);
+
+        let mut invalid = samples.clone();
+        invalid.push(Sample::from_raw_parts(bitvec![0; 16], 1, 3));
+        let before = batch.predict_batch(&samples);
+        assert!(matches!(
+            batch.try_fit_batch(&invalid),
+            Err(WisardError::UnknownLabel(_)),
+        ));
+        assert_eq!(batch.predict_batch(&samples), before);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_batch_is_bit_identical() {
+        let samples = (0..512usize)
+            .map(|i| {
+                let bits: BitVec =
+                    (0..64).map(|j| (i * 7 + j * j) % 5 < 2).collect();
+                Sample::from_raw_parts(bits, 1, i % 6)
+            })
+            .collect::<Vec<_>>();
+
+        let labels = HashSet::from_iter(0usize..6);
+        let seed = [9u8; 32];
+        let mut sequential =
+            CountingWisard::with_seed(64, 8, 4, labels.clone(), seed);
+        let mut parallel = CountingWisard::with_seed(64, 8, 4, labels, seed);
+
+        for sample in samples.iter() {
+            sequential.fit(sample);
+        }
+
+        parallel.fit_batch(&samples);
+
+        for (label, disc) in sequential.base.discriminators() {
+            let found = parallel.base.disc[label].filters();
+            assert_eq!(disc.filters(), found);
+        }
+
+        let expected = samples
+            .iter()
+            .map(|sample| sequential.predict(sample))
+            .collect::<Vec<_>>();
+        assert_eq!(parallel.predict_batch(&samples), expected);
+    }
+
+    #[test]
+    fn counting_wisard_unfit() {
+        let labels = HashSet::from_iter([0usize, 1]);
+        let mut model = CountingWisard::with_seed(8, 2, 2, labels, [7u8; 32]);
+        let sample =
+            Sample::from_raw_parts(bitvec![1, 1, 0, 0, 1, 0, 1, 1], 1, 0usize);
+        let relabeled = Sample::from_raw_parts(sample.raw_bits().into(), 1, 1);
+
+        model.fit(&sample);
+        assert_eq!(model.predict(&sample), 0);
+
+        model.unfit(&sample);
+        model.fit(&relabeled);
+        let mut scores = model.scores(&sample);
+        scores.sort_by_key(|(_, label)| *label);
+        assert_eq!(scores, vec![(0, 0), (4, 1)]);
+
+        model.unfit(&relabeled);
+        model.unfit(&relabeled);
+        let mut scores = model.scores(&sample);
+        scores.sort_by_key(|(_, label)| *label);
+        assert_eq!(scores, vec![(0, 0), (0, 1)]);
+
+        let unknown = Sample::from_raw_parts(bitvec![0; 8], 1, 2);
+        assert!(matches!(
+            model.try_unfit(&unknown),
+            Err(WisardError::UnknownLabel(_)),
+        ));
+    }
+
+    #[test]
+    fn wisard_base_merge() {
+        let samples = round_trip_samples();
+        let labels = HashSet::from_iter(0usize..3);
+        let seed = [9u8; 32];
+        let mut full = CountingWisard::with_seed(8, 2, 3, labels.clone(), seed);
+        let mut left = CountingWisard::with_seed(8, 2, 3, labels.clone(), seed);
+        let mut right =
+            CountingWisard::with_seed(8, 2, 3, labels.clone(), seed);
+
+        for (i, sample) in samples.iter().enumerate() {
+            full.fit(sample);
+            match i % 2 {
+                0 => left.fit(sample),
+                _ => right.fit(sample),
+            }
+        }
+
+        left.merge(&right);
+
+        for sample in samples.iter() {
+            let mut expected = full.scores(sample);
+            let mut found = left.scores(sample);
+            expected.sort_by_key(|(_, label)| *label);
+            found.sort_by_key(|(_, label)| *label);
+            assert_eq!(expected, found);
+        }
+
+        let other_seed = CountingWisard::with_seed(8, 2, 3, labels, [1u8; 32]);
+        assert!(matches!(
+            left.try_merge(&other_seed),
+            Err(WisardError::IncompatibleMerge(_)),
+        ));
+
+        let other_labels =
+            CountingWisard::with_seed(8, 2, 3, HashSet::from([0, 1, 3]), seed);
+        assert!(matches!(
+            left.try_merge(&other_labels),
+            Err(WisardError::IncompatibleMerge(_)),
+        ));
     }
 }
@@ -4,8 +4,20 @@ use bitvec::{prelude::*, ptr::Const};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 /// A trait for the sample labels.
+///
+/// Labels are ordered so that the models can break ties between equal scores
+/// in favor of the smallest label, regardless of the order of the labels.
 pub trait Label:
-    Copy + Clone + Debug + Eq + PartialEq + Hash + Serialize + DeserializeOwned
+    Copy
+    + Clone
+    + Debug
+    + Eq
+    + PartialEq
+    + Ord
+    + PartialOrd
+    + Hash
+    + Serialize
+    + DeserializeOwned
 {
 }
 
@@ -15,6 +27,8 @@ impl<
             + Debug
             + Eq
             + PartialEq
+            + Ord
+            + PartialOrd
             + Hash
             + Serialize
             + DeserializeOwned,
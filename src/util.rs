@@ -2,6 +2,9 @@ use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 
 use num_traits::{Saturating, Unsigned};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{WisardError, WisardResult};
 
 /// A trait for primitive unsigned integers to be used as saturating counters.
 pub trait Counter:
@@ -17,6 +20,8 @@ pub trait Counter:
     + Unsigned
     + Saturating
     + Into<usize>
+    + Serialize
+    + DeserializeOwned
 {
 }
 
@@ -33,29 +38,53 @@ impl<T> Counter for T where
         + Unsigned
         + Saturating
         + Into<usize>
+        + Serialize
+        + DeserializeOwned
 {
 }
 
 /// A hasher that only accepts integers and use their raw values as indices.
+///
+/// Misusing the hasher does not panic immediately. Instead, the first error
+/// is recorded and reported by [`try_finish`](#method.try_finish), while
+/// [`finish`](#method.finish) panics with the recorded error.
 #[derive(Copy, Clone, Debug, Default)]
-pub struct RawIntHasher(Option<u64>);
+pub struct RawIntHasher {
+    value: Option<u64>,
+    error: Option<&'static str>,
+}
+
+impl RawIntHasher {
+    /// Returns the hashed value, or an error if the hasher was misused.
+    pub fn try_finish(&self) -> WisardResult<u64> {
+        match (self.error, self.value) {
+            (Some(reason), _) => Err(WisardError::InvalidAddress(reason)),
+            (None, Some(value)) => Ok(value),
+            (None, None) => Err(WisardError::InvalidAddress(
+                "RawIntHasher have not hashed any values",
+            )),
+        }
+    }
+
+    fn fail(&mut self, reason: &'static str) {
+        self.error.get_or_insert(reason);
+    }
+}
 
 impl Hasher for RawIntHasher {
     fn finish(&self) -> u64 {
-        self.0.unwrap_or_else(|| {
-            panic!("RawIntHasher have not hashed any values")
-        })
+        self.try_finish().unwrap_or_else(|err| panic!("{err}"))
     }
 
     fn write(&mut self, _: &[u8]) {
-        panic!("RawIntHasher can only hash integers");
+        self.fail("RawIntHasher can only hash integers");
     }
 
     fn write_u64(&mut self, i: u64) {
-        self.0.map_or_else(
-            || self.0 = Some(i),
-            |_| panic!("RawIntHasher can only hash once"),
-        )
+        match self.value {
+            Some(_) => self.fail("RawIntHasher can only hash once"),
+            None => self.value = Some(i),
+        }
     }
 
     fn write_u32(&mut self, i: u32) {
@@ -74,3 +103,44 @@ impl Hasher for RawIntHasher {
         self.write_u64(i as u64)
     }
 }
+
+/// Applies a fallible function to every sample of a batch, in parallel when
+/// the `rayon` feature is enabled, preserving the order of the results.
+pub(crate) fn map_batch<S, R, G>(samples: &[S], f: G) -> WisardResult<Vec<R>>
+where
+    S: Sync,
+    R: Send,
+    G: Fn(&S) -> WisardResult<R> + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        samples.par_iter().map(f).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        samples.iter().map(f).collect()
+    }
+}
+
+/// Hashes an item with [`RawIntHasher`](./struct.RawIntHasher.html) and
+/// returns its raw value as an index.
+pub(crate) fn raw_index<T: Hash>(item: &T) -> WisardResult<usize> {
+    let mut hasher = RawIntHasher::default();
+    item.hash(&mut hasher);
+    hasher.try_finish().map(|index| index as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_int_hasher_misuse() {
+        assert_eq!(raw_index(&42usize).unwrap(), 42);
+        assert!(raw_index(&"a").is_err());
+        assert!(raw_index(&(1u8, 2u8)).is_err());
+        assert!(RawIntHasher::default().try_finish().is_err());
+    }
+}